@@ -0,0 +1,74 @@
+//! Times the parallel scanner against a plain `walkdir` walk, the way scans
+//! were done before, on a synthetic tree. Both must agree on the totals.
+//!
+//! Run with `cargo bench --bench walker`.
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 5;
+
+/// 8 top-level directories, each with three levels of 4 subdirectories below it
+/// and 20 files per directory: 13,600 files in 680 directories.
+fn build_tree(root: &Path) {
+    fn fill(dir: &Path, depth: usize, seed: &mut u64) {
+        fs::create_dir_all(dir).unwrap();
+        for i in 0..20 {
+            *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let size = (*seed >> 33) % 4096;
+            fs::write(dir.join(format!("file{i}.bin")), vec![0u8; size as usize]).unwrap();
+        }
+        if depth < 4 {
+            for i in 0..4 {
+                fill(&dir.join(format!("sub{i}")), depth + 1, seed);
+            }
+        }
+    }
+
+    let mut seed = 42;
+    for i in 0..8 {
+        fill(&root.join(format!("top{i}")), 1, &mut seed);
+    }
+}
+
+fn walkdir_totals(root: &Path) -> (u64, u64) {
+    walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .fold((0, 0), |(size, count), m| (size + m.len(), count + 1))
+}
+
+/// Fastest of `RUNS` runs, so the result isn't skewed by a cold cache.
+fn time(name: &str, run: impl Fn() -> (u64, u64)) -> (u64, u64) {
+    let mut best = Duration::MAX;
+    let mut totals = (0, 0);
+    for _ in 0..RUNS {
+        let started = Instant::now();
+        totals = run();
+        best = best.min(started.elapsed());
+    }
+    println!(
+        "{name:<24} {best:>10.2?}  {} bytes, {} files",
+        totals.0, totals.1
+    );
+    totals
+}
+
+fn main() {
+    let dir = tempfile::tempdir().unwrap();
+    build_tree(dir.path());
+    let root = dir.path().to_str().unwrap();
+
+    let reference = time("walkdir", || walkdir_totals(dir.path()));
+    for workers in [1, 4, 0] {
+        let label = match workers {
+            0 => "scanner (all cores)".to_string(),
+            n => format!("scanner ({n} workers)"),
+        };
+        let totals = time(&label, || mist_lib::bench::scan_totals(root, workers));
+        assert_eq!(totals, reference, "{label} disagrees with walkdir");
+    }
+}
@@ -0,0 +1,347 @@
+use crate::thresholds;
+use crate::types::{
+    Alert, AlertCondition, AlertLevel, AlertState, AppSettings, MonitorConfig, ThresholdLevel,
+};
+
+/// Moves a monitor's threshold alert on for its size now being `mb`, and
+/// returns the level to notify about if this update calls for a toast.
+///
+/// The level only drops once the size is `hysteresis_percent` of the
+/// threshold below it, so a folder hovering around a level doesn't flap.
+/// Reaching a higher level counts as the alert starting again.
+pub fn update(
+    alert: &mut Alert,
+    monitor: &MonitorConfig,
+    settings: &AppSettings,
+    mb: f64,
+    now: u64,
+) -> Option<ThresholdLevel> {
+    let levels = thresholds::levels(monitor);
+    let mut level = thresholds::level_of(monitor, mb);
+    if alert.threshold.is_active() && level < alert.level {
+        let holding = levels
+            .iter()
+            .find(|l| l.level == alert.level)
+            .is_some_and(|l| {
+                mb > monitor.threshold * (l.percent - settings.hysteresis_percent) / 100.0
+            });
+        if holding {
+            level = alert.level;
+        }
+    }
+
+    let was_active = any_active(alert);
+    let worse = alert.threshold.is_active() && level > alert.level;
+    alert.level = level;
+    let reached = levels.into_iter().find(|l| l.level == level && l.notify);
+    let notify = monitor.notify && reached.is_some();
+    let due = step(
+        &mut alert.threshold,
+        alert.snoozed_until,
+        level != AlertLevel::Ok,
+        worse,
+        notify,
+        settings,
+        now,
+    );
+    end_snooze_if_resolved(alert, was_active);
+    reached.filter(|_| due)
+}
+
+/// Moves a monitor's growth alert on for it having grown `grown_mb` over the
+/// alert's window, and returns whether this update calls for a toast.
+pub fn update_growth(
+    alert: &mut Alert,
+    monitor: &MonitorConfig,
+    settings: &AppSettings,
+    grown_mb: Option<f64>,
+    now: u64,
+) -> bool {
+    let holds = monitor
+        .growth_alert
+        .as_ref()
+        .filter(|g| g.mb > 0.0 && g.hours > 0)
+        .zip(grown_mb)
+        .is_some_and(|(growth, grown_mb)| grown_mb > growth.mb);
+    let was_active = any_active(alert);
+    let due = step(
+        &mut alert.growth,
+        alert.snoozed_until,
+        holds,
+        false,
+        monitor.notify,
+        settings,
+        now,
+    );
+    end_snooze_if_resolved(alert, was_active);
+    due
+}
+
+/// Moves a monitor's file count alert on for it now holding `count` files,
+/// and returns whether this update calls for a toast. Like the threshold, the
+/// alert only clears once the count is `hysteresis_percent` below the limit.
+pub fn update_file_count(
+    alert: &mut Alert,
+    monitor: &MonitorConfig,
+    settings: &AppSettings,
+    count: u64,
+    now: u64,
+) -> bool {
+    let holds = monitor
+        .max_file_count
+        .filter(|max| *max > 0)
+        .is_some_and(|max| {
+            let margin = match alert.file_count.is_active() {
+                true => settings.hysteresis_percent,
+                false => 0.0,
+            };
+            count as f64 > max as f64 * (100.0 - margin) / 100.0
+        });
+    let was_active = any_active(alert);
+    let due = step(
+        &mut alert.file_count,
+        alert.snoozed_until,
+        holds,
+        false,
+        monitor.notify,
+        settings,
+        now,
+    );
+    end_snooze_if_resolved(alert, was_active);
+    due
+}
+
+fn any_active(alert: &Alert) -> bool {
+    [&alert.threshold, &alert.growth, &alert.file_count]
+        .iter()
+        .any(|condition| condition.is_active())
+}
+
+/// A snooze only covers the alerts it was meant for: once the last of them
+/// has cleared, the next one to fire notifies straight away.
+fn end_snooze_if_resolved(alert: &mut Alert, was_active: bool) {
+    if was_active && !any_active(alert) {
+        alert.snoozed_until = None;
+    }
+}
+
+/// Moves one of a monitor's alerts on for whether its condition `holds` now,
+/// and returns whether a toast is due, marking it as sent if so.
+///
+/// A toast is due when the alert starts or gets `worse`, when a snooze runs
+/// out, and every `renotify_minutes` while it stays unacknowledged. Nothing is
+/// sent while the monitor is snoozed or `notify` is off.
+fn step(
+    condition: &mut AlertCondition,
+    snoozed_until: Option<u64>,
+    holds: bool,
+    worse: bool,
+    notify: bool,
+    settings: &AppSettings,
+    now: u64,
+) -> bool {
+    let active = condition.is_active();
+    if !holds {
+        if active {
+            condition.state = AlertState::Resolved;
+            condition.changed_at = now;
+            condition.notified_at = None;
+        }
+        return false;
+    }
+
+    let entered = !active || worse;
+    if entered {
+        condition.state = AlertState::Firing;
+        condition.changed_at = now;
+    }
+
+    let snoozed = snoozed_until.is_some_and(|until| until > now);
+    // Not notified since the snooze ran out, so whatever it held back is still owed
+    let snooze_ended = snoozed_until
+        .is_some_and(|until| until <= now && condition.notified_at.is_none_or(|at| at < until));
+    let renotify_due = settings.renotify_minutes > 0
+        && condition
+            .notified_at
+            .is_none_or(|at| now >= at + settings.renotify_minutes as u64 * 60);
+    let due = entered || (condition.state == AlertState::Firing && (snooze_ended || renotify_due));
+    if !due || snoozed || !notify {
+        return false;
+    }
+
+    condition.notified_at = Some(now);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::GrowthAlert;
+
+    const HOUR: u64 = 60 * 60;
+
+    fn monitor() -> MonitorConfig {
+        MonitorConfig {
+            threshold: 1000.0,
+            notify: true,
+            growth_alert: Some(GrowthAlert {
+                mb: 100.0,
+                hours: 24,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn settings() -> AppSettings {
+        AppSettings {
+            renotify_minutes: 60,
+            ..Default::default()
+        }
+    }
+
+    fn level_after(alert: &mut Alert, mb: f64, now: u64) -> Option<AlertLevel> {
+        update(alert, &monitor(), &settings(), mb, now).map(|l| l.level)
+    }
+
+    #[test]
+    fn threshold_notifies_on_entering_and_reminds_until_acknowledged() {
+        let mut alert = Alert::default();
+        assert_eq!(level_after(&mut alert, 500.0, 0), None);
+        // Warning doesn't notify by default, critical does
+        assert_eq!(level_after(&mut alert, 900.0, 10), None);
+        assert_eq!(alert.level, AlertLevel::Warning);
+        assert_eq!(
+            level_after(&mut alert, 1100.0, 20),
+            Some(AlertLevel::Critical)
+        );
+        assert_eq!(level_after(&mut alert, 1100.0, 30), None);
+        assert_eq!(
+            level_after(&mut alert, 1100.0, 20 + HOUR),
+            Some(AlertLevel::Critical)
+        );
+
+        alert.threshold.state = AlertState::Acknowledged;
+        assert_eq!(level_after(&mut alert, 1100.0, 20 + 3 * HOUR), None);
+    }
+
+    #[test]
+    fn threshold_holds_within_the_margin_and_then_resolves() {
+        let mut alert = Alert::default();
+        level_after(&mut alert, 1100.0, 0);
+        // Within 5% below the level
+        level_after(&mut alert, 960.0, 10);
+        assert_eq!(alert.level, AlertLevel::Critical);
+        level_after(&mut alert, 500.0, 20);
+        assert_eq!(alert.level, AlertLevel::Ok);
+        assert_eq!(alert.threshold.state, AlertState::Resolved);
+        assert_eq!(
+            level_after(&mut alert, 1100.0, 30),
+            Some(AlertLevel::Critical)
+        );
+    }
+
+    #[test]
+    fn snoozed_notifications_are_sent_once_the_snooze_ends() {
+        let mut alert = Alert {
+            snoozed_until: Some(HOUR),
+            ..Default::default()
+        };
+        assert_eq!(level_after(&mut alert, 1100.0, 10), None);
+        assert!(!update_growth(
+            &mut alert,
+            &monitor(),
+            &settings(),
+            Some(200.0),
+            10
+        ));
+
+        assert_eq!(
+            level_after(&mut alert, 1100.0, HOUR + 10),
+            Some(AlertLevel::Critical)
+        );
+        assert!(update_growth(
+            &mut alert,
+            &monitor(),
+            &settings(),
+            Some(200.0),
+            HOUR + 10
+        ));
+        assert_eq!(level_after(&mut alert, 1100.0, HOUR + 20), None);
+    }
+
+    #[test]
+    fn snoozes_end_once_the_alerts_clear() {
+        let mut alert = Alert {
+            snoozed_until: Some(HOUR),
+            ..Default::default()
+        };
+        assert_eq!(level_after(&mut alert, 1100.0, 10), None);
+        assert!(!update_growth(
+            &mut alert,
+            &monitor(),
+            &settings(),
+            Some(200.0),
+            10
+        ));
+
+        // The growth alert still firing keeps the snooze
+        assert_eq!(level_after(&mut alert, 500.0, 20), None);
+        assert_eq!(alert.snoozed_until, Some(HOUR));
+        update_growth(&mut alert, &monitor(), &settings(), Some(50.0), 30);
+        assert_eq!(alert.snoozed_until, None);
+
+        assert_eq!(
+            level_after(&mut alert, 1100.0, 40),
+            Some(AlertLevel::Critical)
+        );
+    }
+
+    #[test]
+    fn growth_alerts_move_through_the_same_states() {
+        let mut alert = Alert::default();
+        let grew = |alert: &mut Alert, mb, now| {
+            update_growth(alert, &monitor(), &settings(), Some(mb), now)
+        };
+        assert!(!grew(&mut alert, 50.0, 0));
+        assert!(grew(&mut alert, 150.0, 10));
+        assert_eq!(alert.growth.state, AlertState::Firing);
+        assert!(!grew(&mut alert, 150.0, 20));
+        assert!(grew(&mut alert, 150.0, 10 + HOUR));
+
+        alert.growth.state = AlertState::Acknowledged;
+        assert!(!grew(&mut alert, 150.0, 10 + 3 * HOUR));
+        assert!(!grew(&mut alert, 50.0, 10 + 4 * HOUR));
+        assert_eq!(alert.growth.state, AlertState::Resolved);
+        // The threshold alert is untouched
+        assert_eq!(alert.threshold, AlertCondition::default());
+    }
+
+    #[test]
+    fn file_count_alerts_fire_once_per_crossing() {
+        let monitor = MonitorConfig {
+            max_file_count: Some(1000),
+            ..monitor()
+        };
+        let mut alert = Alert::default();
+        let counted = |alert: &mut Alert, count, now| {
+            update_file_count(alert, &monitor, &settings(), count, now)
+        };
+        assert!(!counted(&mut alert, 1000, 0));
+        assert!(counted(&mut alert, 1001, 10));
+        assert!(!counted(&mut alert, 1200, 20));
+        // Within the margin below the limit it keeps firing
+        assert!(!counted(&mut alert, 990, 30));
+        assert_eq!(alert.file_count.state, AlertState::Firing);
+        assert!(!counted(&mut alert, 900, 40));
+        assert_eq!(alert.file_count.state, AlertState::Resolved);
+        assert!(counted(&mut alert, 1001, 50));
+
+        // A crossing during a snooze is still reported once it ends
+        let mut alert = Alert {
+            snoozed_until: Some(HOUR),
+            ..Default::default()
+        };
+        assert!(!counted(&mut alert, 1001, 10));
+        assert!(counted(&mut alert, 1001, HOUR + 10));
+    }
+}
@@ -0,0 +1,55 @@
+use serde::Serialize;
+use std::fmt;
+use std::io::{self, ErrorKind};
+
+/// Error returned by commands, serialized as `{ kind, message }` so the UI can
+/// tell a missing folder from a permission problem or a bad config file.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum MistError {
+    Io(String),
+    Parse(String),
+    PathNotFound(String),
+    PermissionDenied(String),
+    InvalidConfig(String),
+    /// A scan couldn't start, failed, or was cancelled.
+    Scan(String),
+}
+
+impl MistError {
+    /// Classifies an IO error, naming the path it happened on.
+    pub fn io(path: impl fmt::Display, error: io::Error) -> Self {
+        match error.kind() {
+            ErrorKind::NotFound => Self::PathNotFound(path.to_string()),
+            ErrorKind::PermissionDenied => Self::PermissionDenied(path.to_string()),
+            _ => Self::Io(format!("{}: {}", path, error)),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Io(message)
+            | Self::Parse(message)
+            | Self::PathNotFound(message)
+            | Self::PermissionDenied(message)
+            | Self::InvalidConfig(message)
+            | Self::Scan(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for MistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PathNotFound(path) => write!(f, "Path not found: {}", path),
+            Self::PermissionDenied(path) => write!(f, "Permission denied: {}", path),
+            _ => f.write_str(self.message()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for MistError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Parse(error.to_string())
+    }
+}
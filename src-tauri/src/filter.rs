@@ -0,0 +1,151 @@
+use regex::Regex;
+use std::path::Path;
+
+/// Exclude and include glob patterns for one scan.
+///
+/// Patterns without a `/` match an entry's name anywhere in the tree (`*.tmp`,
+/// `node_modules`); patterns with one match the path relative to the scan root
+/// (`cache/huge`, `**/*.log`). `*` and `?` stay within one path segment and
+/// `**` spans any number of them. Matching ignores case on Windows.
+///
+/// Excluded directories are not descended into. When there are include
+/// patterns, only files matching one of them are counted.
+#[derive(Debug, Default)]
+pub struct PathFilter {
+    exclude: GlobList,
+    include: GlobList,
+}
+
+impl PathFilter {
+    pub fn new(exclude: &[String], include: &[String]) -> Result<Self, String> {
+        Ok(Self {
+            exclude: GlobList::compile(exclude)?,
+            include: GlobList::compile(include)?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exclude.is_empty() && self.include.is_empty()
+    }
+
+    /// `relative` is the directory's path below the scan root.
+    pub fn skips_dir(&self, relative: &Path) -> bool {
+        self.exclude.matches(relative)
+    }
+
+    /// `relative` is the file's path below the scan root.
+    pub fn skips_file(&self, relative: &Path) -> bool {
+        self.exclude.matches(relative)
+            || (!self.include.is_empty() && !self.include.matches(relative))
+    }
+}
+
+/// Name patterns and relative-path patterns, each folded into a single regex.
+#[derive(Debug, Default)]
+struct GlobList {
+    names: Option<Regex>,
+    paths: Option<Regex>,
+}
+
+impl GlobList {
+    fn compile(patterns: &[String]) -> Result<Self, String> {
+        let mut names = Vec::new();
+        let mut paths = Vec::new();
+        for pattern in patterns {
+            let pattern = pattern.trim().replace('\\', "/");
+            let pattern = pattern.trim_matches('/');
+            if pattern.is_empty() {
+                continue;
+            }
+
+            let regex = glob_to_regex(pattern);
+            // Checked one by one so the error can name the bad pattern
+            Regex::new(&regex).map_err(|_| format!("Invalid pattern: {}", pattern))?;
+            if pattern.contains('/') {
+                paths.push(regex);
+            } else {
+                names.push(regex);
+            }
+        }
+
+        Ok(Self {
+            names: alternation(&names)?,
+            paths: alternation(&paths)?,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.names.is_none() && self.paths.is_none()
+    }
+
+    fn matches(&self, relative: &Path) -> bool {
+        if let Some(names) = &self.names {
+            let name = relative.file_name().unwrap_or_default().to_string_lossy();
+            if names.is_match(&name) {
+                return true;
+            }
+        }
+        if let Some(paths) = &self.paths {
+            let joined = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if paths.is_match(&joined) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+fn alternation(parts: &[String]) -> Result<Option<Regex>, String> {
+    if parts.is_empty() {
+        return Ok(None);
+    }
+
+    let flags = if cfg!(windows) { "(?i)" } else { "" };
+    Regex::new(&format!("{}^(?:{})$", flags, parts.join("|")))
+        .map(Some)
+        .map_err(|e| format!("Invalid pattern: {}", e))
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                // "**/" also matches no directories at all
+                if chars.get(i + 2) == Some(&'/') {
+                    out.push_str("(?:.*/)?");
+                    i += 1;
+                } else {
+                    out.push_str(".*");
+                }
+                i += 1;
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => match chars[i + 1..].iter().position(|c| *c == ']') {
+                Some(len) if len > 0 => {
+                    let class: String = chars[i + 1..i + 1 + len].iter().collect();
+                    let class = match class.strip_prefix('!') {
+                        Some(rest) => format!("^{}", rest),
+                        None => class,
+                    };
+                    out.push('[');
+                    out.push_str(&class.replace('\\', "\\\\").replace('[', "\\["));
+                    out.push(']');
+                    i += len + 1;
+                }
+                // No closing bracket, so it's a literal
+                _ => out.push_str("\\["),
+            },
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    out
+}
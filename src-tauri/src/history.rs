@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+/// Every scan is kept for this long, then folded into hourly points.
+const RAW_RETENTION_SECS: u64 = 7 * DAY;
+
+/// Growth rates are fitted over this much recent history.
+const GROWTH_WINDOW_SECS: u64 = 7 * DAY;
+
+/// Hourly points are kept for this long, then folded into daily points.
+const HOURLY_RETENTION_SECS: u64 = 90 * DAY;
+
+/// The result of one completed scan, or the average of several once
+/// downsampled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPoint {
+    /// Time of the scan, or the start of the hour or day it stands for.
+    pub timestamp: u64,
+    pub size_bytes: u64,
+    pub file_count: u64,
+    pub duration_ms: u64,
+    /// Number of scans averaged into this point.
+    pub samples: u32,
+}
+
+/// Size history of one monitor, oldest first.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct History {
+    pub points: Vec<HistoryPoint>,
+}
+
+/// How far back `get_monitor_history` looks.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryRange {
+    Day,
+    Week,
+    Month,
+    Year,
+    All,
+}
+
+impl HistoryRange {
+    fn secs(self) -> Option<u64> {
+        match self {
+            Self::Day => Some(DAY),
+            Self::Week => Some(7 * DAY),
+            Self::Month => Some(30 * DAY),
+            Self::Year => Some(365 * DAY),
+            Self::All => None,
+        }
+    }
+}
+
+impl History {
+    /// Adds a scan result and downsamples points that have aged out of
+    /// their resolution.
+    pub fn record(&mut self, point: HistoryPoint, now: u64) {
+        let at = self
+            .points
+            .partition_point(|p| p.timestamp <= point.timestamp);
+        self.points.insert(at, point);
+        self.compact(now);
+    }
+
+    pub fn range(&self, range: HistoryRange, now: u64) -> Vec<HistoryPoint> {
+        let since = range.secs().map_or(0, |secs| now.saturating_sub(secs));
+        self.range_since(since).to_vec()
+    }
+
+    /// Bytes per day, from a least-squares fit over the last week of points.
+    /// `None` until there are at least two points an hour or more apart.
+    pub fn growth_rate(&self, now: u64) -> Option<f64> {
+        let recent = self.range_since(now.saturating_sub(GROWTH_WINDOW_SECS));
+        let (first, last) = (recent.first()?, recent.last()?);
+        if last.timestamp - first.timestamp < HOUR {
+            return None;
+        }
+
+        // Relative to the first point so the squares stay small
+        let n = recent.len() as f64;
+        let xs = recent
+            .iter()
+            .map(|p| (p.timestamp - first.timestamp) as f64 / DAY as f64);
+        let ys = recent.iter().map(|p| p.size_bytes as f64);
+        let mean_x = xs.clone().sum::<f64>() / n;
+        let mean_y = ys.clone().sum::<f64>() / n;
+        let (mut covariance, mut variance) = (0.0, 0.0);
+        for (x, y) in xs.zip(ys) {
+            covariance += (x - mean_x) * (y - mean_y);
+            variance += (x - mean_x) * (x - mean_x);
+        }
+
+        (variance > 0.0).then(|| covariance / variance)
+    }
+
+    /// How much the size changed over the last `window_secs`, measured from
+    /// the oldest point inside the window to the newest.
+    pub fn growth_within(&self, window_secs: u64, now: u64) -> Option<i64> {
+        let recent = self.range_since(now.saturating_sub(window_secs));
+        let (first, last) = (recent.first()?, recent.last()?);
+        if first.timestamp == last.timestamp {
+            return None;
+        }
+        Some(last.size_bytes as i64 - first.size_bytes as i64)
+    }
+
+    fn range_since(&self, since: u64) -> &[HistoryPoint] {
+        let start = self.points.partition_point(|p| p.timestamp < since);
+        &self.points[start..]
+    }
+
+    /// Merges points older than the raw retention into one per hour, and
+    /// points older than the hourly retention into one per day.
+    fn compact(&mut self, now: u64) {
+        let mut compacted: Vec<HistoryPoint> = Vec::with_capacity(self.points.len());
+        let mut last_bucket = None;
+
+        for point in self.points.drain(..) {
+            let age = now.saturating_sub(point.timestamp);
+            let width = if age >= HOURLY_RETENTION_SECS {
+                DAY
+            } else if age >= RAW_RETENTION_SECS {
+                HOUR
+            } else {
+                compacted.push(point);
+                last_bucket = None;
+                continue;
+            };
+
+            let bucket = (width, point.timestamp / width);
+            match compacted.last_mut() {
+                Some(previous) if last_bucket == Some(bucket) => merge(previous, &point),
+                _ => compacted.push(HistoryPoint {
+                    timestamp: bucket.1 * width,
+                    ..point
+                }),
+            }
+            last_bucket = Some(bucket);
+        }
+
+        self.points = compacted;
+    }
+}
+
+/// Folds `point` into `into`, weighting each by how many scans it stands for.
+fn merge(into: &mut HistoryPoint, point: &HistoryPoint) {
+    let a = into.samples.max(1) as u128;
+    let b = point.samples.max(1) as u128;
+    let average = |x: u64, y: u64| ((x as u128 * a + y as u128 * b) / (a + b)) as u64;
+
+    into.size_bytes = average(into.size_bytes, point.size_bytes);
+    into.file_count = average(into.file_count, point.file_count);
+    into.duration_ms = average(into.duration_ms, point.duration_ms);
+    into.samples = into.samples.max(1) + point.samples.max(1);
+}
+
+/// When a monitor growing at `bytes_per_day` will pass `threshold_bytes`, as
+/// a Unix time. `None` if it isn't growing or is already over.
+pub fn threshold_eta(
+    size_bytes: u64,
+    threshold_bytes: u64,
+    bytes_per_day: f64,
+    now: u64,
+) -> Option<u64> {
+    if bytes_per_day <= 0.0 || size_bytes >= threshold_bytes {
+        return None;
+    }
+    let days = (threshold_bytes - size_bytes) as f64 / bytes_per_day;
+    Some(now + (days * DAY as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 200 * DAY;
+
+    fn point(timestamp: u64, size_bytes: u64) -> HistoryPoint {
+        HistoryPoint {
+            timestamp,
+            size_bytes,
+            file_count: size_bytes / 10,
+            duration_ms: 100,
+            samples: 1,
+        }
+    }
+
+    fn history(points: &[(u64, u64)]) -> History {
+        let mut history = History::default();
+        for (timestamp, size_bytes) in points {
+            history.record(point(*timestamp, *size_bytes), NOW);
+        }
+        history
+    }
+
+    #[test]
+    fn old_points_are_folded_into_hours_and_then_days() {
+        let history = history(&[
+            // Over 90 days old: one per day
+            (NOW - 100 * DAY + HOUR, 100),
+            (NOW - 100 * DAY + 5 * HOUR, 300),
+            // Over 7 days old: one per hour
+            (NOW - 10 * DAY + 60, 1000),
+            (NOW - 10 * DAY + 120, 2000),
+            (NOW - 10 * DAY + 180, 3000),
+            (NOW - 10 * DAY + HOUR, 4000),
+            // Recent: every scan
+            (NOW - 60, 5000),
+            (NOW - 30, 6000),
+        ]);
+
+        let points: Vec<_> = history
+            .points
+            .iter()
+            .map(|p| (p.timestamp, p.size_bytes, p.samples))
+            .collect();
+        assert_eq!(
+            points,
+            [
+                (NOW - 100 * DAY, 200, 2),
+                (NOW - 10 * DAY, 2000, 3),
+                (NOW - 10 * DAY + HOUR, 4000, 1),
+                (NOW - 60, 5000, 1),
+                (NOW - 30, 6000, 1),
+            ]
+        );
+        assert_eq!(history.points[1].file_count, 200);
+    }
+
+    #[test]
+    fn merged_points_keep_their_weight() {
+        let mut history = history(&[(NOW - 10 * DAY, 1000), (NOW - 10 * DAY + 60, 2000)]);
+        // Three scans in that hour now, so the first two count for two thirds
+        history.record(point(NOW - 10 * DAY + 120, 4000), NOW);
+        assert_eq!(history.points.len(), 1);
+        assert_eq!(history.points[0].size_bytes, 2333);
+        assert_eq!(history.points[0].samples, 3);
+    }
+
+    #[test]
+    fn growth_rate_fits_the_last_week() {
+        assert_eq!(history(&[]).growth_rate(NOW), None);
+        // Too close together to say anything
+        assert_eq!(
+            history(&[(NOW - 60, 1000), (NOW, 5000)]).growth_rate(NOW),
+            None
+        );
+
+        let steady = history(&[
+            (NOW - 3 * DAY, 1000),
+            (NOW - 2 * DAY, 2000),
+            (NOW - DAY, 3000),
+            (NOW, 4000),
+        ]);
+        assert_eq!(steady.growth_rate(NOW), Some(1000.0));
+
+        // Points older than the week don't count
+        let mut shrinking = history(&[(NOW - 20 * DAY, 100_000)]);
+        shrinking.record(point(NOW - 2 * DAY, 3000), NOW);
+        shrinking.record(point(NOW, 1000), NOW);
+        assert_eq!(shrinking.growth_rate(NOW), Some(-1000.0));
+    }
+
+    #[test]
+    fn threshold_eta_projects_the_growth_rate() {
+        assert_eq!(threshold_eta(1000, 2000, 500.0, NOW), Some(NOW + 2 * DAY));
+        assert_eq!(threshold_eta(1000, 2000, 0.0, NOW), None);
+        assert_eq!(threshold_eta(1000, 2000, -500.0, NOW), None);
+        assert_eq!(threshold_eta(2000, 2000, 500.0, NOW), None);
+    }
+}
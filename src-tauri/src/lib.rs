@@ -1,203 +1,511 @@
+use std::collections::HashMap;
 use std::fs;
 
-use std::time::Duration;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
 use tauri::menu::{Menu, MenuItem};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
 use tauri::{Emitter, Manager};
-use walkdir::WalkDir;
 use window_vibrancy::apply_mica;
 
+mod alerts;
 mod defaults;
+mod error;
+mod filter;
+mod history;
+mod scan_registry;
+mod scanner;
+mod schedule;
+mod snapshot;
+mod state;
 mod storage;
+mod thresholds;
 mod types;
 mod utils;
-
+mod walker;
+mod watcher;
+mod worker;
+
+use error::MistError;
+use history::{History, HistoryPoint, HistoryRange};
+use scan_registry::ScanRegistry;
+use scanner::Scanner;
+use state::AppState;
+use storage::ParseError;
 pub use types::*;
-
-fn scan_directory(path: &str, max_depth: Option<usize>) -> Result<(u64, u64), String> {
-    let expanded_path = utils::expand_env_vars(path);
-    let path_buf = std::path::PathBuf::from(&expanded_path);
-
-    if !path_buf.exists() {
-        return Err("Path not found".to_string());
+use watcher::WatchRegistry;
+use worker::{WorkerCommand, WorkerControl};
+
+/// Lets `benches/` time the scanner without making it part of the app's API.
+#[doc(hidden)]
+pub mod bench {
+    /// Total size and file count under `root`, as a full scan counts them.
+    pub fn scan_totals(root: &str, workers: usize) -> (u64, u64) {
+        let summary = crate::Scanner::new(root)
+            .workers(workers)
+            .run(&|_, _| {})
+            .expect("scan failed");
+        (summary.size_bytes, summary.file_count)
     }
+}
 
-    let mut total_size = 0;
-    let mut file_count = 0;
+/// Runs `scanner` for `monitor_id`, streaming `scan-progress` events while it walks
+/// and a final `done` event when it finishes or is cancelled.
+/// Errors are returned without emitting so each caller decides how to report them.
+fn scan_with_progress(
+    app_handle: &tauri::AppHandle,
+    monitor_id: &str,
+    scanner: &Scanner,
+) -> Result<ScanProgress, String> {
+    let started = Instant::now();
+    let summary = scanner.run(&|size_bytes, file_count| {
+        app_handle
+            .emit(
+                "scan-progress",
+                ScanProgress {
+                    monitor_id: monitor_id.to_string(),
+                    size_bytes,
+                    file_count,
+                    ..Default::default()
+                },
+            )
+            .ok();
+    })?;
+
+    let state = app_handle.state::<AppState>();
+    let monitor = state.monitor(monitor_id);
+    // Only saved monitors keep a snapshot and history, not a folder scanned
+    // before it was added
+    let completed = monitor.is_some() && !summary.cancelled;
+    if completed {
+        if let Some(snapshot) = &summary.snapshot {
+            storage::save_snapshot(app_handle, monitor_id, snapshot);
+        }
+        // Manual scans count too, so the schedule starts over from this one
+        let now = utils::now_secs();
+        let settings = state.settings();
+        state.update_monitors(app_handle, |monitors| {
+            if let Some(monitor) = monitors.iter_mut().find(|m| m.id == monitor_id) {
+                monitor.last_scan_at = Some(now);
+                monitor.next_scan_at = Some(schedule::next_scan_at(monitor, &settings, now));
+            }
+        });
+    }
+    let growth = if completed {
+        let history = record_history(app_handle, monitor_id, &summary, started.elapsed());
+        history.growth_rate(utils::now_secs())
+    } else {
+        None
+    };
+    let threshold_eta = growth.zip(monitor.as_ref()).and_then(|(rate, monitor)| {
+        let threshold_bytes = (monitor.threshold * 1024.0 * 1024.0) as u64;
+        history::threshold_eta(summary.size_bytes, threshold_bytes, rate, utils::now_secs())
+    });
+    let level = monitor.filter(|_| !summary.cancelled).map(|monitor| {
+        let mb = summary.size_bytes as f64 / (1024.0 * 1024.0);
+        let (level, notify) = state.update_alert(app_handle, &monitor, mb);
+        if let Some(reached) = notify {
+            show_threshold_toast(app_handle, &monitor, &reached, mb);
+        }
+        level
+    });
 
-    let walker = match max_depth {
-        Some(d) if d > 0 => WalkDir::new(&path_buf).max_depth(d),
-        _ => WalkDir::new(&path_buf),
+    // A cancelled scan reports what it had counted so far
+    let progress = ScanProgress {
+        monitor_id: monitor_id.to_string(),
+        size_bytes: summary.size_bytes,
+        file_count: summary.file_count,
+        done: true,
+        cancelled: summary.cancelled,
+        error: None,
+        last_scan_at: if summary.cancelled {
+            None
+        } else {
+            Some(utils::now_secs())
+        },
+        largest_files: summary.largest_files,
+        extensions: summary.extensions,
+        age_buckets: summary.age_buckets,
+        stale_bytes: summary.stale_bytes,
+        logical_bytes: Some(summary.logical_bytes),
+        allocated_bytes: Some(summary.allocated_bytes),
+        hardlink_bytes_saved: summary.hardlink_bytes_saved,
+        partial: summary.skipped_count > 0,
+        skipped_count: Some(summary.skipped_count),
+        skipped_samples: Some(summary.skipped_samples),
+        reused_dirs: Some(summary.reused_dirs),
+        growth_bytes_per_day: growth,
+        threshold_eta,
+        level,
+        live: false,
     };
+    app_handle.emit("scan-progress", progress.clone()).ok();
 
-    for entry in walker.into_iter().filter_map(|e| e.ok()) {
-        if entry.file_type().is_file() {
-            file_count += 1;
-            if let Ok(metadata) = entry.metadata() {
-                total_size += metadata.len();
-            }
-        }
-    }
+    Ok(progress)
+}
 
-    Ok((total_size, file_count))
+#[tauri::command]
+fn get_settings(state: tauri::State<'_, AppState>) -> AppSettings {
+    state.settings()
 }
 
 #[tauri::command]
-fn get_settings(app_handle: tauri::AppHandle) -> AppSettings {
-    storage::load_settings(&app_handle)
+fn save_settings(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    settings: AppSettings,
+) -> Result<(), MistError> {
+    state.set_settings(&app_handle, settings)
 }
 
 #[tauri::command]
-fn save_settings(app_handle: tauri::AppHandle, settings: AppSettings) {
-    storage::save_settings_to_file(&app_handle, &settings);
+fn get_monitors(state: tauri::State<'_, AppState>) -> Vec<MonitorConfig> {
+    state.monitors()
 }
 
 #[tauri::command]
-fn get_monitors(app_handle: tauri::AppHandle) -> Vec<MonitorConfig> {
-    storage::load_monitors_from_file(&app_handle)
+fn save_monitors(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    monitors: Vec<MonitorConfig>,
+) -> Result<(), MistError> {
+    state.set_monitors(&app_handle, monitors)
 }
 
+/// Problems found loading the config files, such as one restored from a backup.
 #[tauri::command]
-fn save_monitors(app_handle: tauri::AppHandle, monitors: Vec<MonitorConfig>) {
-    storage::save_monitors_to_file(&app_handle, &monitors);
+fn get_storage_warnings(state: tauri::State<'_, AppState>) -> Vec<String> {
+    state.take_warnings()
 }
 
 #[tauri::command]
-fn check_monitor_path(path: String, max_depth: Option<usize>) -> CheckResult {
-    match scan_directory(&path, max_depth) {
-        Ok((size_bytes, file_count)) => CheckResult {
-            size_bytes,
-            file_count,
+fn check_monitor_path(
+    app_handle: tauri::AppHandle,
+    path: String,
+    max_depth: Option<usize>,
+    extension_stats: Option<bool>,
+    size_mode: Option<SizeMode>,
+) -> CheckResult {
+    let settings = app_handle.state::<AppState>().settings();
+    let scanner = Scanner::new(&path)
+        .max_depth(max_depth)
+        .workers(settings.scan_workers as usize)
+        .extension_stats(extension_stats.unwrap_or(false))
+        .size_mode(size_mode.unwrap_or_default());
+
+    match scanner.run(&|_, _| {}) {
+        Ok(summary) => CheckResult {
+            size_bytes: summary.size_bytes,
+            file_count: summary.file_count,
             error: None,
+            extensions: summary.extensions,
+            logical_bytes: summary.logical_bytes,
+            allocated_bytes: summary.allocated_bytes,
+            skipped_count: summary.skipped_count,
+            skipped_samples: summary.skipped_samples,
         },
         Err(e) => CheckResult {
-            size_bytes: 0,
-            file_count: 0,
             error: Some(e),
+            ..Default::default()
         },
     }
 }
 
+/// Starts a background scan that reports through `scan-progress` events.
+/// Returns `false` without starting anything if this monitor is already being scanned.
 #[tauri::command]
 fn check_monitor_path_streaming(
     app_handle: tauri::AppHandle,
+    registry: tauri::State<'_, ScanRegistry>,
     monitor_id: String,
     path: String,
     max_depth: Option<usize>,
-) {
-    std::thread::spawn(move || {
-        let expanded_path = utils::expand_env_vars(&path);
-        let path_buf = std::path::PathBuf::from(&expanded_path);
+) -> bool {
+    let guard = match registry.begin(&monitor_id) {
+        Some(guard) => guard,
+        None => return false,
+    };
+
+    let settings = app_handle.state::<AppState>().settings();
+    let mut scanner = Scanner::new(&path)
+        .max_depth(max_depth)
+        .workers(settings.scan_workers as usize)
+        .cancel_token(guard.token().clone());
+    // Path and depth come from the caller, which may be mid-edit; everything else from the saved monitor
+    if let Ok(monitor) = find_monitor(&app_handle, &monitor_id) {
+        // Always a full walk, which also refreshes the snapshot for background scans
+        scanner = scanner
+            .monitor_options(&monitor)
+            .record_snapshot(monitor.incremental);
+    }
 
-        if !path_buf.exists() {
+    std::thread::spawn(move || {
+        if let Err(e) = scan_with_progress(&app_handle, &monitor_id, &scanner) {
             app_handle
                 .emit(
                     "scan-progress",
                     ScanProgress {
                         monitor_id,
-                        size_bytes: 0,
-                        file_count: 0,
                         done: true,
-                        error: Some("Path not found".to_string()),
-                        last_scan_at: None,
+                        error: Some(e),
+                        ..Default::default()
                     },
                 )
                 .ok();
-            return;
         }
+        drop(guard);
+    });
 
-        let mut total_size: u64 = 0;
-        let mut file_count: u64 = 0;
+    true
+}
 
-        let walker = match max_depth {
-            Some(d) if d > 0 => WalkDir::new(&path_buf).max_depth(d),
-            _ => WalkDir::new(&path_buf),
-        };
+#[tauri::command]
+fn cancel_scan(registry: tauri::State<'_, ScanRegistry>, monitor_id: String) -> bool {
+    registry.cancel(&monitor_id)
+}
 
-        for entry in walker.into_iter().filter_map(|e| e.ok()) {
-            if entry.file_type().is_file() {
-                file_count += 1;
-                if let Ok(metadata) = entry.metadata() {
-                    total_size += metadata.len();
-                }
-                // Emit progress every 500 files
-                if file_count % 500 == 0 {
-                    app_handle
-                        .emit(
-                            "scan-progress",
-                            ScanProgress {
-                                monitor_id: monitor_id.clone(),
-                                size_bytes: total_size,
-                                file_count,
-                                done: false,
-                                error: None,
-                                last_scan_at: None,
-                            },
-                        )
-                        .ok();
-                }
-            }
+/// Runs a background check of every enabled monitor right away.
+#[tauri::command]
+fn trigger_background_check(control: tauri::State<'_, WorkerControl>) {
+    control.send(WorkerCommand::CheckNow);
+}
+
+/// Pauses background scans and watches for `duration` minutes, or until
+/// resumed if `None`.
+#[tauri::command]
+fn pause_monitoring(control: tauri::State<'_, WorkerControl>, duration: Option<u64>) {
+    let until = duration.map(|minutes| utils::now_secs() + minutes * 60);
+    control.send(WorkerCommand::Pause(until));
+}
+
+#[tauri::command]
+fn resume_monitoring(control: tauri::State<'_, WorkerControl>) {
+    control.send(WorkerCommand::Resume);
+}
+
+#[tauri::command]
+fn get_worker_status(control: tauri::State<'_, WorkerControl>) -> WorkerStatus {
+    control.status()
+}
+
+#[tauri::command]
+fn get_alerts(state: tauri::State<'_, AppState>) -> HashMap<String, Alert> {
+    state.alerts()
+}
+
+/// Holds back notifications for a monitor for `hours`, or until its alerts
+/// have all cleared, so the next one to fire notifies again.
+#[tauri::command]
+fn snooze_alert(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    monitor_id: String,
+    hours: u32,
+) -> Result<(), MistError> {
+    let until = utils::now_secs() + hours as u64 * 60 * 60;
+    state.change_alert(&app_handle, &monitor_id, |alert| {
+        alert.snoozed_until = Some(until)
+    })
+}
+
+/// Stops reminders for a firing alert. It notifies again if it gets worse.
+#[tauri::command]
+fn acknowledge_alert(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    monitor_id: String,
+) -> Result<(), MistError> {
+    state.change_alert(&app_handle, &monitor_id, acknowledge)
+}
+
+fn acknowledge(alert: &mut Alert) {
+    for condition in [
+        &mut alert.threshold,
+        &mut alert.growth,
+        &mut alert.file_count,
+    ] {
+        if condition.state == AlertState::Firing {
+            condition.state = AlertState::Acknowledged;
         }
+    }
+}
 
-        // Final result
-        app_handle
-            .emit(
-                "scan-progress",
-                ScanProgress {
-                    monitor_id,
-                    size_bytes: total_size,
-                    file_count,
-                    done: true,
-                    error: None,
-                    last_scan_at: Some(
-                        std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs(),
-                    ),
-                },
-            )
-            .ok();
-    });
+/// Adds a completed scan to the monitor's size history, returning the history.
+fn record_history(
+    app_handle: &tauri::AppHandle,
+    monitor_id: &str,
+    summary: &scanner::ScanSummary,
+    duration: Duration,
+) -> History {
+    let now = utils::now_secs();
+    let mut history = storage::load_history(app_handle, monitor_id);
+    history.record(
+        HistoryPoint {
+            timestamp: now,
+            size_bytes: summary.size_bytes,
+            file_count: summary.file_count,
+            duration_ms: duration.as_millis() as u64,
+            samples: 1,
+        },
+        now,
+    );
+    if let Err(e) = storage::save_history(app_handle, monitor_id, &history) {
+        app_handle.emit("storage-error", e).ok();
+    }
+    history
 }
 
+/// Size history of a monitor over `range`, oldest first. Older stretches come
+/// back as hourly or daily averages.
 #[tauri::command]
-fn open_monitor_path(_app_handle: tauri::AppHandle, path: String) -> Result<(), String> {
+fn get_monitor_history(
+    app_handle: tauri::AppHandle,
+    id: String,
+    range: HistoryRange,
+) -> Result<Vec<HistoryPoint>, MistError> {
+    find_monitor(&app_handle, &id)?;
+    Ok(storage::load_history(&app_handle, &id).range(range, utils::now_secs()))
+}
+
+/// Validates a cron expression, returning when it would next fire.
+#[tauri::command]
+fn check_schedule(expression: String) -> Result<u64, MistError> {
+    schedule::next_cron_run(&expression, utils::now_secs()).map_err(MistError::InvalidConfig)
+}
+
+fn find_monitor(
+    app_handle: &tauri::AppHandle,
+    monitor_id: &str,
+) -> Result<MonitorConfig, MistError> {
+    app_handle
+        .state::<AppState>()
+        .monitor(monitor_id)
+        .ok_or_else(|| MistError::InvalidConfig("Monitor not found".to_string()))
+}
+
+/// Runs a one-off scan of a saved monitor off the main thread.
+/// Registered like any other scan, so it is deduplicated and can be stopped with `cancel_scan`.
+async fn scan_monitor_once(
+    app_handle: &tauri::AppHandle,
+    registry: &ScanRegistry,
+    monitor_id: &str,
+    configure: impl FnOnce(Scanner) -> Scanner,
+) -> Result<scanner::ScanSummary, MistError> {
+    let monitor = find_monitor(app_handle, monitor_id)?;
+    let expanded_path = utils::expand_env_vars(&monitor.path);
+    if !std::path::Path::new(&expanded_path).exists() {
+        return Err(MistError::PathNotFound(expanded_path));
+    }
+    let guard = registry
+        .begin(monitor_id)
+        .ok_or_else(|| MistError::Scan("A scan of this folder is already running".to_string()))?;
+
+    let settings = app_handle.state::<AppState>().settings();
+    let scanner = configure(
+        Scanner::for_monitor(&monitor)
+            .workers(settings.scan_workers as usize)
+            .cancel_token(guard.token().clone()),
+    );
+
+    let summary = tauri::async_runtime::spawn_blocking(move || {
+        let summary = scanner.run(&|_, _| {});
+        drop(guard);
+        summary
+    })
+    .await
+    .map_err(|e| MistError::Scan(e.to_string()))?
+    .map_err(MistError::Scan)?;
+
+    if summary.cancelled {
+        return Err(MistError::Scan("Scan cancelled".to_string()));
+    }
+    Ok(summary)
+}
+
+/// Walks a monitor and returns its subdirectories down to `depth` levels, largest first.
+#[tauri::command]
+async fn get_size_breakdown(
+    app_handle: tauri::AppHandle,
+    registry: tauri::State<'_, ScanRegistry>,
+    monitor_id: String,
+    depth: usize,
+) -> Result<SizeNode, MistError> {
+    scan_monitor_once(&app_handle, &registry, &monitor_id, |s| {
+        s.breakdown_depth(depth)
+    })
+    .await?
+    .breakdown
+    .ok_or_else(|| MistError::Scan("Breakdown not available".to_string()))
+}
+
+/// Walks a monitor and returns its `limit` largest files, largest first.
+#[tauri::command]
+async fn get_largest_files(
+    app_handle: tauri::AppHandle,
+    registry: tauri::State<'_, ScanRegistry>,
+    monitor_id: String,
+    limit: usize,
+) -> Result<Vec<LargeFile>, MistError> {
+    let summary = scan_monitor_once(&app_handle, &registry, &monitor_id, |s| {
+        s.largest_files(limit.max(1))
+    })
+    .await?;
+    Ok(summary.largest_files.unwrap_or_default())
+}
+
+#[tauri::command]
+fn open_monitor_path(_app_handle: tauri::AppHandle, path: String) -> Result<(), MistError> {
     let expanded_path = utils::expand_env_vars(&path);
-    tauri_plugin_opener::open_path(expanded_path, None::<&str>).map_err(|e| e.to_string())
+    if !std::path::Path::new(&expanded_path).exists() {
+        return Err(MistError::PathNotFound(expanded_path));
+    }
+    tauri_plugin_opener::open_path(expanded_path, None::<&str>)
+        .map_err(|e| MistError::Io(e.to_string()))
 }
 
 #[tauri::command]
-fn restore_defaults(app_handle: tauri::AppHandle) -> Vec<MonitorConfig> {
+fn restore_defaults(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<MonitorConfig>, MistError> {
     let monitors = defaults::get_default_monitors();
-    storage::save_monitors_to_file(&app_handle, &monitors);
-    monitors
+    state.set_monitors(&app_handle, monitors)?;
+    Ok(state.monitors())
 }
 
 #[tauri::command]
-fn open_config_folder(app_handle: tauri::AppHandle) -> Result<(), String> {
-    let config_path = app_handle.path().app_config_dir().unwrap();
+fn open_config_folder(app_handle: tauri::AppHandle) -> Result<(), MistError> {
+    let config_path = storage::get_config_dir(&app_handle)?;
     if !config_path.exists() {
-        fs::create_dir_all(&config_path).ok();
+        fs::create_dir_all(&config_path).map_err(|e| MistError::io(config_path.display(), e))?;
     }
     tauri_plugin_opener::open_path(config_path.to_string_lossy().to_string(), None::<&str>)
-        .map_err(|e| e.to_string())
+        .map_err(|e| MistError::Io(e.to_string()))
 }
 
 #[tauri::command]
-fn export_monitors(app_handle: tauri::AppHandle, path: String) -> Result<(), String> {
-    let monitors = storage::load_monitors_from_file(&app_handle);
-    let content = serde_json::to_string_pretty(&monitors).map_err(|e| e.to_string())?;
-    fs::write(path, content).map_err(|e| e.to_string())?;
+fn export_monitors(state: tauri::State<'_, AppState>, path: String) -> Result<(), MistError> {
+    let monitors = storage::monitors_json(&state.monitors());
+    let content = serde_json::to_string_pretty(&monitors)?;
+    fs::write(&path, content).map_err(|e| MistError::io(&path, e))?;
     Ok(())
 }
 
 #[tauri::command]
-fn import_monitors(app_handle: tauri::AppHandle, path: String) -> Result<(), String> {
-    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let monitors: Vec<MonitorConfig> =
-        serde_json::from_str(&content).map_err(|_| "Invalid config file".to_string())?;
-    storage::save_monitors_to_file(&app_handle, &monitors);
+fn import_monitors(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<(), MistError> {
+    let content = fs::read_to_string(&path).map_err(|e| MistError::io(&path, e))?;
+    let monitors = storage::parse_monitors(&content).map_err(|e| match e {
+        ParseError::Newer { .. } => MistError::InvalidConfig(e.to_string()),
+        ParseError::Invalid(e) => MistError::InvalidConfig(format!("Invalid config file: {}", e)),
+    })?;
+    for monitor in &monitors {
+        storage::check_monitor_id(&monitor.id)?;
+    }
+    state.update_monitors(&app_handle, |current| *current = monitors);
     Ok(())
 }
 
@@ -251,6 +559,7 @@ fn test_notification(
             )));
 
         toast.action(Action::new("Open Folder", "open", ""));
+        toast.action(Action::new("Snooze 1 Hour", "snooze", ""));
         toast.action(Action::new("Mute Notifications", "mute", ""));
 
         let _ = manager.show_with_callbacks(
@@ -263,14 +572,146 @@ fn test_notification(
                                 .arg(&path_for_callback)
                                 .spawn();
                         }
-                        "mute" => {
-                            let mut monitors = storage::load_monitors_from_file(&app_clone);
-                            if let Some(m) = monitors.iter_mut().find(|m| m.id == id_clone) {
-                                m.notify = false;
-                                storage::save_monitors_to_file(&app_clone, &monitors);
-                                app_clone.emit("monitors-updated", ()).ok();
+                        "snooze" => snooze_monitor(&app_clone, &id_clone),
+                        "mute" => mute_monitor(&app_clone, &id_clone),
+                        _ => {}
+                    }
+                }
+            })),
+            None,
+            None,
+        );
+    });
+}
+
+/// Snoozes a monitor's alert for an hour from a toast's snooze action.
+fn snooze_monitor(app_handle: &tauri::AppHandle, monitor_id: &str) {
+    let until = utils::now_secs() + 60 * 60;
+    let snoozed = app_handle
+        .state::<AppState>()
+        .change_alert(app_handle, monitor_id, |alert| {
+            alert.snoozed_until = Some(until)
+        });
+    if let Err(e) = snoozed {
+        app_handle.emit("storage-error", e).ok();
+    }
+}
+
+/// Turns off notifications for a monitor from a toast's mute action.
+fn mute_monitor(app_handle: &tauri::AppHandle, monitor_id: &str) {
+    app_handle
+        .state::<AppState>()
+        .update_monitors(app_handle, |monitors| {
+            if let Some(m) = monitors.iter_mut().find(|m| m.id == monitor_id) {
+                m.notify = false;
+                m.muted_at = Some(utils::now_secs());
+            }
+        });
+}
+
+/// Shows a toast for a monitor that has reached `level`.
+fn show_threshold_toast(
+    app_handle: &tauri::AppHandle,
+    monitor: &MonitorConfig,
+    level: &ThresholdLevel,
+    mb: f64,
+) {
+    let title = match level.level {
+        AlertLevel::Critical => format!("{} exceeded limit!", monitor.name),
+        _ => format!("{} is nearing its limit", monitor.name),
+    };
+    let body = level.message.clone().unwrap_or_else(|| {
+        format!(
+            "Size: {:.1} GB / {:.1} GB threshold",
+            mb / 1024.0,
+            monitor.threshold / 1024.0
+        )
+    });
+    show_monitor_toast(app_handle, monitor, title, body);
+}
+
+/// Shows a toast for a monitor that grew faster than its growth alert allows.
+fn show_growth_toast(app_handle: &tauri::AppHandle, monitor: &MonitorConfig, grown_mb: f64) {
+    let hours = monitor.growth_alert.as_ref().map_or(0, |a| a.hours);
+    show_monitor_toast(
+        app_handle,
+        monitor,
+        format!("{} is growing fast!", monitor.name),
+        format!("Grew {:.0} MB in the last {} hours", grown_mb, hours),
+    );
+}
+
+/// Shows a toast for a monitor holding more files than it should.
+fn show_file_count_toast(app_handle: &tauri::AppHandle, monitor: &MonitorConfig, count: u64) {
+    show_monitor_toast(
+        app_handle,
+        monitor,
+        format!("{} has too many files!", monitor.name),
+        format!(
+            "{} files / {} limit",
+            count,
+            monitor.max_file_count.unwrap_or_default()
+        ),
+    );
+}
+
+/// How many MB a monitor grew over its growth alert's window, if it has one
+/// and the history covers it.
+fn growth_over_window(app_handle: &tauri::AppHandle, monitor: &MonitorConfig) -> Option<f64> {
+    let alert = monitor
+        .growth_alert
+        .as_ref()
+        .filter(|a| a.mb > 0.0 && a.hours > 0)?;
+    let grown = storage::load_history(app_handle, &monitor.id)
+        .growth_within(alert.hours as u64 * 60 * 60, utils::now_secs())?;
+    Some(grown as f64 / (1024.0 * 1024.0))
+}
+
+/// Shows a toast about a monitor, with actions to open the folder or mute
+/// the monitor.
+fn show_monitor_toast(
+    app_handle: &tauri::AppHandle,
+    monitor: &MonitorConfig,
+    title: String,
+    body: String,
+) {
+    let expanded_path = utils::expand_env_vars(&monitor.path);
+    let path_clone = expanded_path.clone();
+    let app_clone = app_handle.clone();
+    let id_clone = monitor.id.clone();
+
+    std::thread::spawn(move || {
+        use winrt_toast::{Action, Text, Toast, ToastManager};
+
+        let manager = ToastManager::new("com.fastfingertips.mist");
+
+        let mut toast = Toast::new();
+        toast.text1(&title).text2(Text::new(&body));
+
+        toast.action(Action::new("Open Folder", "open", ""));
+        toast.action(Action::new("Snooze 1 Hour", "snooze", ""));
+        toast.action(Action::new("Mute Notifications", "mute", ""));
+
+        let _ = manager.show_with_callbacks(
+            &toast,
+            Some(Box::new(move |e| {
+                if let Ok(arg) = e {
+                    match arg.as_str() {
+                        "open" | "" => {
+                            let _ = std::process::Command::new("explorer")
+                                .arg(&path_clone)
+                                .spawn();
+                            let acknowledged = app_clone.state::<AppState>().change_alert(
+                                &app_clone,
+                                &id_clone,
+                                acknowledge,
+                            );
+                            if let Err(e) = acknowledged {
+                                app_clone.emit("storage-error", e).ok();
                             }
                         }
+                        "snooze" => snooze_monitor(&app_clone, &id_clone),
+                        "mute" => mute_monitor(&app_clone, &id_clone),
                         _ => {}
                     }
                 }
@@ -281,127 +722,118 @@ fn test_notification(
     });
 }
 
-fn start_background_worker(app_handle: tauri::AppHandle) {
+fn start_background_worker(app_handle: tauri::AppHandle, commands: Receiver<WorkerCommand>) {
+    let registry = app_handle.state::<ScanRegistry>().inner().clone();
+    let watches = app_handle.state::<WatchRegistry>().inner().clone();
+    let control = app_handle.state::<WorkerControl>().inner().clone();
+    let state = app_handle.state::<AppState>().inner().clone();
+    let mut check_now = false;
+
     loop {
-        let settings = storage::load_settings(&app_handle);
-        let mut monitors = storage::load_monitors_from_file(&app_handle);
-        let mut changed = false;
-
-        for monitor in monitors.iter_mut() {
-            if monitor.enabled {
-                app_handle
-                    .emit(
-                        "scan-progress",
-                        ScanProgress {
-                            monitor_id: monitor.id.clone(),
-                            size_bytes: 0,
-                            file_count: 0,
-                            done: false,
-                            error: None,
-                            last_scan_at: None,
-                        },
-                    )
-                    .ok();
-
-                if let Ok((size_bytes, file_count)) =
-                    scan_directory(&monitor.path, monitor.max_depth)
-                {
-                    let mb = size_bytes as f64 / (1024.0 * 1024.0);
-                    let now = std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs();
-
-                    monitor.last_scan_at = Some(now);
-                    changed = true;
-
-                    app_handle
-                        .emit(
-                            "scan-progress",
-                            ScanProgress {
-                                monitor_id: monitor.id.clone(),
-                                size_bytes,
-                                file_count,
-                                done: true,
-                                error: None,
-                                last_scan_at: Some(now),
-                            },
+        let settings = state.settings();
+        let monitors = state.monitors();
+        let status = control.refresh(&app_handle);
+        watches.set_paused(status.paused);
+        watches.sync(&app_handle, &monitors);
+
+        let now = utils::now_secs();
+        let mut scanned: Vec<(String, u64)> = Vec::new();
+
+        for monitor in monitors.iter() {
+            let due = check_now || (!status.paused && schedule::due_at(monitor, &settings) <= now);
+            if !monitor.enabled || !due {
+                continue;
+            }
+
+            // Cancelled, failed and skipped scans all wait for the next slot
+            // rather than retrying right away
+            let next = || schedule::next_scan_at(monitor, &settings, utils::now_secs());
+
+            // A manual scan of this monitor is already running and will report itself
+            let guard = match registry.begin(&monitor.id) {
+                Some(guard) => guard,
+                None => {
+                    scanned.push((monitor.id.clone(), next()));
+                    continue;
+                }
+            };
+
+            let mut scanner = Scanner::for_monitor(monitor)
+                .workers(settings.scan_workers as usize)
+                .cancel_token(guard.token().clone());
+            if monitor.incremental {
+                scanner = scanner
+                    .snapshot(storage::load_snapshot(&app_handle, &monitor.id))
+                    .record_snapshot(true);
+            }
+
+            match scan_with_progress(&app_handle, &monitor.id, &scanner) {
+                Ok(progress) if !progress.cancelled => {
+                    let grown_mb = growth_over_window(&app_handle, monitor);
+                    let notify = state.check_alert(&app_handle, &monitor.id, |alert, settings| {
+                        alerts::update_growth(alert, monitor, settings, grown_mb, utils::now_secs())
+                    });
+                    if let Some(grown_mb) = grown_mb.filter(|_| notify) {
+                        show_growth_toast(&app_handle, monitor, grown_mb);
+                    }
+
+                    let notify = state.check_alert(&app_handle, &monitor.id, |alert, settings| {
+                        alerts::update_file_count(
+                            alert,
+                            monitor,
+                            settings,
+                            progress.file_count,
+                            utils::now_secs(),
                         )
-                        .ok();
-
-                    if monitor.notify && mb > monitor.threshold {
-                        let expanded_path = utils::expand_env_vars(&monitor.path);
-                        let name = monitor.name.clone();
-                        let threshold = monitor.threshold;
-                        let path_clone = expanded_path.clone();
-                        let app_clone = app_handle.clone();
-                        let id_clone = monitor.id.clone();
-
-                        std::thread::spawn(move || {
-                            use winrt_toast::{Action, Text, Toast, ToastManager};
-
-                            let manager = ToastManager::new("com.fastfingertips.mist");
-
-                            let mut toast = Toast::new();
-                            toast
-                                .text1(&format!("{} exceeded limit!", name))
-                                .text2(Text::new(&format!(
-                                    "Size: {:.1} GB / {:.1} GB threshold",
-                                    mb / 1024.0,
-                                    threshold / 1024.0
-                                )));
-
-                            toast.action(Action::new("Open Folder", "open", ""));
-                            toast.action(Action::new("Mute Notifications", "mute", ""));
-
-                            let _ = manager.show_with_callbacks(
-                                &toast,
-                                Some(Box::new(move |e| {
-                                    if let Ok(arg) = e {
-                                        match arg.as_str() {
-                                            "open" | "" => {
-                                                let _ = std::process::Command::new("explorer")
-                                                    .arg(&path_clone)
-                                                    .spawn();
-                                            }
-                                            "mute" => {
-                                                let mut monitors =
-                                                    storage::load_monitors_from_file(&app_clone);
-                                                if let Some(m) =
-                                                    monitors.iter_mut().find(|m| m.id == id_clone)
-                                                {
-                                                    m.notify = false;
-                                                    storage::save_monitors_to_file(
-                                                        &app_clone, &monitors,
-                                                    );
-                                                    app_clone.emit("monitors-updated", ()).ok();
-                                                }
-                                            }
-                                            _ => {}
-                                        }
-                                    }
-                                })),
-                                None,
-                                None,
-                            );
-                        });
+                    });
+                    if notify {
+                        show_file_count_toast(&app_handle, monitor, progress.file_count);
                     }
                 }
+                _ => {}
             }
+            scanned.push((monitor.id.clone(), next()));
         }
-
-        if changed {
-            storage::save_monitors_to_file(&app_handle, &monitors);
+        check_now = false;
+
+        if !scanned.is_empty() {
+            // Only the next scan time, so edits made while scanning aren't
+            // overwritten; completed scans have already saved their last scan time
+            state.update_monitors(&app_handle, |monitors| {
+                for monitor in monitors.iter_mut() {
+                    if let Some((_, next)) = scanned.iter().find(|(id, _)| *id == monitor.id) {
+                        monitor.next_scan_at = Some(*next);
+                    }
+                }
+            });
+            app_handle
+                .emit("background-check-complete", utils::now_secs())
+                .ok();
         }
 
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        app_handle.emit("background-check-complete", now).ok();
+        // Wake for the next due monitor or the end of a pause, but check at
+        // least once a minute so edits and newly added monitors are picked up
+        let next_due = if status.paused {
+            status.paused_until.unwrap_or(u64::MAX)
+        } else {
+            monitors
+                .iter()
+                .filter(|m| m.enabled && !scanned.iter().any(|(id, _)| *id == m.id))
+                .map(|m| schedule::due_at(m, &settings))
+                .chain(scanned.iter().map(|(_, next)| *next))
+                .min()
+                .unwrap_or(u64::MAX)
+        };
+        let wait = next_due.saturating_sub(utils::now_secs()).clamp(1, 60);
 
-        let interval_secs = (settings.check_interval_minutes as u64) * 60;
-        std::thread::sleep(Duration::from_secs(interval_secs));
+        match commands.recv_timeout(Duration::from_secs(wait)) {
+            Ok(command) => check_now = control.apply(&app_handle, command),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        while let Ok(command) = commands.try_recv() {
+            check_now |= control.apply(&app_handle, command);
+        }
     }
 }
 
@@ -412,14 +844,23 @@ pub fn run() {
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .manage(ScanRegistry::default())
+        .manage(WatchRegistry::default())
         .setup(|app| {
             let app_handle = app.handle().clone();
+            app.manage(AppState::load(&app_handle));
+            let (control, commands) = WorkerControl::new();
+            app.manage(control);
 
             let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
             let show_i = MenuItem::with_id(app, "show", "Show Mist", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_i, &quit_i])?;
+            let check_i = MenuItem::with_id(app, "check", "Scan Now", true, None::<&str>)?;
+            let pause_i = MenuItem::with_id(app, "pause", "Pause for 1 Hour", true, None::<&str>)?;
+            let resume_i = MenuItem::with_id(app, "resume", "Resume", true, None::<&str>)?;
+            let menu = Menu::with_items(app, &[&show_i, &check_i, &pause_i, &resume_i, &quit_i])?;
 
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id("main")
+                .tooltip("Mist")
                 .icon(app.default_window_icon().unwrap().clone())
                 .menu(&menu)
                 .on_menu_event(|app: &tauri::AppHandle, event| match event.id.as_ref() {
@@ -432,6 +873,13 @@ pub fn run() {
                             let _ = window.set_focus();
                         }
                     }
+                    "check" => app.state::<WorkerControl>().send(WorkerCommand::CheckNow),
+                    "pause" => {
+                        let until = utils::now_secs() + 60 * 60;
+                        app.state::<WorkerControl>()
+                            .send(WorkerCommand::Pause(Some(until)));
+                    }
+                    "resume" => app.state::<WorkerControl>().send(WorkerCommand::Resume),
                     _ => {}
                 })
                 .on_tray_icon_event(|tray: &tauri::tray::TrayIcon, event| {
@@ -451,7 +899,7 @@ pub fn run() {
                 .build(app)?;
 
             std::thread::spawn(move || {
-                start_background_worker(app_handle);
+                start_background_worker(app_handle, commands);
             });
 
             let window = app.get_webview_window("main").unwrap();
@@ -468,7 +916,7 @@ pub fn run() {
         })
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                let settings = storage::load_settings(window.app_handle());
+                let settings = window.app_handle().state::<AppState>().settings();
                 if settings.minimize_to_tray {
                     api.prevent_close();
                     window.hide().unwrap();
@@ -477,9 +925,22 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             get_monitors,
+            get_storage_warnings,
             save_monitors,
             check_monitor_path,
             check_monitor_path_streaming,
+            cancel_scan,
+            check_schedule,
+            trigger_background_check,
+            pause_monitoring,
+            resume_monitoring,
+            get_worker_status,
+            get_alerts,
+            snooze_alert,
+            acknowledge_alert,
+            get_size_breakdown,
+            get_largest_files,
+            get_monitor_history,
             open_monitor_path,
             restore_defaults,
             open_config_folder,
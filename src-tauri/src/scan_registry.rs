@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Cooperative cancellation flag shared between a running scan and `cancel_scan`.
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Scans currently in flight, keyed by monitor id.
+#[derive(Clone, Default)]
+pub struct ScanRegistry {
+    scans: Arc<Mutex<HashMap<String, CancelToken>>>,
+}
+
+impl ScanRegistry {
+    /// Registers a scan for `monitor_id`.
+    /// Returns `None` if that monitor is already being scanned.
+    pub fn begin(&self, monitor_id: &str) -> Option<ScanGuard> {
+        let mut scans = self.scans.lock().unwrap();
+        if scans.contains_key(monitor_id) {
+            return None;
+        }
+
+        let token = CancelToken::default();
+        scans.insert(monitor_id.to_string(), token.clone());
+
+        Some(ScanGuard {
+            registry: self.clone(),
+            monitor_id: monitor_id.to_string(),
+            token,
+        })
+    }
+
+    /// Requests cancellation of the running scan, if any.
+    pub fn cancel(&self, monitor_id: &str) -> bool {
+        match self.scans.lock().unwrap().get(monitor_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Keeps a scan registered until dropped.
+pub struct ScanGuard {
+    registry: ScanRegistry,
+    monitor_id: String,
+    token: CancelToken,
+}
+
+impl ScanGuard {
+    pub fn token(&self) -> &CancelToken {
+        &self.token
+    }
+}
+
+impl Drop for ScanGuard {
+    fn drop(&mut self) {
+        let mut scans = self.registry.scans.lock().unwrap();
+        if let Some(token) = scans.get(&self.monitor_id) {
+            if Arc::ptr_eq(&token.0, &self.token.0) {
+                scans.remove(&self.monitor_id);
+            }
+        }
+    }
+}
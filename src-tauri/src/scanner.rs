@@ -0,0 +1,1023 @@
+use crate::filter::PathFilter;
+use crate::scan_registry::CancelToken;
+use crate::snapshot::{self, DirRecord, Snapshot, MAX_SNAPSHOT_AGE_SECS};
+use crate::types::{
+    AgeBucket, ExtensionStat, LargeFile, MonitorConfig, SizeMode, SizeNode, SkippedEntry,
+};
+use crate::utils;
+use crate::walker::{DirAction, ParallelWalker};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How many files a worker counts between progress reports.
+const PROGRESS_INTERVAL: u64 = 500;
+
+/// Extensions listed individually in the per-extension stats; the rest go to "other".
+const EXTENSION_BUCKETS: usize = 10;
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// Last-modified age buckets as (label, exclusive upper bound in seconds).
+/// Files whose modified time can't be read land in the last bucket.
+const AGE_BUCKETS: [(&str, Option<u64>); 5] = [
+    ("< 1 day", Some(DAY_SECS)),
+    ("< 7 days", Some(7 * DAY_SECS)),
+    ("< 30 days", Some(30 * DAY_SECS)),
+    ("< 1 year", Some(365 * DAY_SECS)),
+    ("Older", None),
+];
+
+/// Receives running totals while a scan is in progress.
+/// Called from the walker threads, so implementations must be `Sync`.
+pub trait ProgressCallback: Sync {
+    fn on_progress(&self, size_bytes: u64, file_count: u64);
+}
+
+impl<F> ProgressCallback for F
+where
+    F: Fn(u64, u64) + Sync,
+{
+    fn on_progress(&self, size_bytes: u64, file_count: u64) {
+        self(size_bytes, file_count)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScanSummary {
+    /// Total in the scanner's `SizeMode`; the other statistics use the same measure.
+    pub size_bytes: u64,
+    pub file_count: u64,
+    pub logical_bytes: u64,
+    pub allocated_bytes: u64,
+    /// The scan was stopped early; the totals only cover what was walked so far.
+    pub cancelled: bool,
+    /// Sizes per subdirectory, present when `breakdown_depth` was set.
+    pub breakdown: Option<SizeNode>,
+    /// Largest files first, present when `largest_files` was set.
+    pub largest_files: Option<Vec<LargeFile>>,
+    /// Bytes and files per extension, present when `extension_stats` was set.
+    pub extensions: Option<Vec<ExtensionStat>>,
+    /// Bytes and files per last-modified age, present when `age_histogram` was set.
+    pub age_buckets: Option<Vec<AgeBucket>>,
+    /// Bytes not modified in `stale_after_days`, present when that was set.
+    pub stale_bytes: Option<u64>,
+    /// Bytes skipped as extra hard links to an already counted file,
+    /// present when `dedupe_hardlinks` was set.
+    pub hardlink_bytes_saved: Option<u64>,
+    /// Entries that couldn't be read, so their sizes are missing from the totals.
+    pub skipped_count: u64,
+    /// The first few of those, with the reason.
+    pub skipped_samples: Vec<SkippedEntry>,
+    /// Directories whose totals came from the previous snapshot instead of being listed.
+    pub reused_dirs: u64,
+    /// What this scan found, present when `record_snapshot` was set and the
+    /// scan can use snapshots at all.
+    pub snapshot: Option<Snapshot>,
+}
+
+/// State shared by every worker for the duration of one `run`.
+struct RunContext<'a> {
+    scanner: &'a Scanner,
+    started_at: SystemTime,
+    /// (device, file id) of multiply-linked files that have already been counted.
+    seen_links: Mutex<HashSet<(u64, u64)>>,
+    /// A snapshot this scan may take unchanged directories from.
+    previous: Option<&'a Snapshot>,
+    /// Whether to record per-directory totals for the next snapshot.
+    recording: bool,
+    /// Allocation unit of the root's volume, see [`cluster_size`].
+    cluster: u64,
+}
+
+/// Per-worker running totals.
+#[derive(Default)]
+struct Tally {
+    size_bytes: u64,
+    file_count: u64,
+    logical_bytes: u64,
+    allocated_bytes: u64,
+    reported_size: u64,
+    reported_count: u64,
+    /// Size and file count keyed by the file's directory, relative to the root
+    /// and truncated to the breakdown depth.
+    dirs: HashMap<PathBuf, (u64, u64)>,
+    largest: TopFiles,
+    /// Size and file count keyed by lowercase extension, empty for none.
+    extensions: HashMap<String, (u64, u64)>,
+    /// Size and file count per `AGE_BUCKETS` entry.
+    ages: [(u64, u64); AGE_BUCKETS.len()],
+    stale_bytes: u64,
+    hardlink_bytes_saved: u64,
+    /// Files directly inside each directory, keyed relative to the root.
+    snapshot_dirs: HashMap<PathBuf, DirRecord>,
+    reused_dirs: u64,
+}
+
+fn add_to<K: Eq + Hash>(map: &mut HashMap<K, (u64, u64)>, key: K, size: u64, count: u64) {
+    let entry = map.entry(key).or_default();
+    entry.0 += size;
+    entry.1 += count;
+}
+
+impl Tally {
+    fn new(scanner: &Scanner) -> Self {
+        Self {
+            largest: TopFiles::new(scanner.largest_files),
+            ..Default::default()
+        }
+    }
+
+    /// Decides whether a directory has to be listed, taking its totals from the
+    /// previous snapshot when it hasn't changed.
+    fn enter(&mut self, ctx: &RunContext, path: &Path) -> DirAction {
+        if !ctx.recording {
+            return DirAction::Read;
+        }
+
+        let relative = path
+            .strip_prefix(&ctx.scanner.root)
+            .unwrap_or(path)
+            .to_path_buf();
+        let mtime_ns = snapshot::dir_mtime_ns(path);
+
+        let unchanged = ctx
+            .previous
+            .zip(mtime_ns)
+            .and_then(|(previous, mtime_ns)| previous.unchanged(&relative, mtime_ns));
+        let Some(record) = unchanged else {
+            self.snapshot_dirs.insert(
+                relative,
+                DirRecord {
+                    mtime_ns: mtime_ns.unwrap_or(0),
+                    ..Default::default()
+                },
+            );
+            return DirAction::Read;
+        };
+
+        self.size_bytes += match ctx.scanner.size_mode {
+            SizeMode::Logical => record.logical_bytes,
+            SizeMode::Allocated => record.allocated_bytes,
+        };
+        self.logical_bytes += record.logical_bytes;
+        self.allocated_bytes += record.allocated_bytes;
+        self.file_count += record.file_count;
+        self.reused_dirs += 1;
+
+        let children = record.subdirs.iter().map(|name| path.join(name)).collect();
+        self.snapshot_dirs.insert(relative, record.clone());
+        DirAction::Reuse(children)
+    }
+
+    /// Accounts for one file in every statistic the scanner asked for.
+    fn record(&mut self, ctx: &RunContext, path: &Path, metadata: Option<&fs::Metadata>) {
+        let scanner = ctx.scanner;
+        let logical = metadata.map(|m| m.len()).unwrap_or(0);
+        let allocated = metadata
+            .map(|m| allocated_size(path, m, ctx.cluster))
+            .unwrap_or(0);
+        let size = match scanner.size_mode {
+            SizeMode::Logical => logical,
+            SizeMode::Allocated => allocated,
+        };
+
+        if scanner.dedupe_hardlinks {
+            if let Some(id) = metadata.and_then(|m| hardlink_id(path, m)) {
+                let first = ctx
+                    .seen_links
+                    .lock()
+                    .map(|mut seen| seen.insert(id))
+                    .unwrap_or(true);
+                if !first {
+                    self.hardlink_bytes_saved += size;
+                    return;
+                }
+            }
+        }
+
+        self.logical_bytes += logical;
+        self.allocated_bytes += allocated;
+        self.file_count += 1;
+        self.size_bytes += size;
+
+        if ctx.recording {
+            let record = path
+                .parent()
+                .and_then(|dir| dir.strip_prefix(&scanner.root).ok())
+                .and_then(|dir| self.snapshot_dirs.get_mut(dir));
+            if let Some(record) = record {
+                record.logical_bytes += logical;
+                record.allocated_bytes += allocated;
+                record.file_count += 1;
+            }
+        }
+
+        if let Some(depth) = scanner.breakdown_depth {
+            let dir = path
+                .parent()
+                .and_then(|p| p.strip_prefix(&scanner.root).ok())
+                .unwrap_or(Path::new(""));
+            add_to(
+                &mut self.dirs,
+                dir.components().take(depth).collect(),
+                size,
+                1,
+            );
+        }
+
+        if scanner.extension_stats {
+            let ext = path
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            add_to(&mut self.extensions, ext, size, 1);
+        }
+
+        let modified = metadata.and_then(|m| m.modified().ok());
+
+        if scanner.age_histogram || scanner.stale_after_days.is_some() {
+            // Files modified "in the future" count as brand new
+            let age = modified.map(|t| {
+                ctx.started_at
+                    .duration_since(t)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0)
+            });
+            let bucket = AGE_BUCKETS
+                .iter()
+                .position(|(_, max)| match (age, max) {
+                    (Some(age), Some(max)) => age < *max,
+                    _ => false,
+                })
+                .unwrap_or(AGE_BUCKETS.len() - 1);
+            self.ages[bucket].0 += size;
+            self.ages[bucket].1 += 1;
+
+            if let (Some(age), Some(days)) = (age, scanner.stale_after_days) {
+                if age >= days as u64 * DAY_SECS {
+                    self.stale_bytes += size;
+                }
+            }
+        }
+
+        if self.largest.admits(size) {
+            self.largest.push(Candidate {
+                size_bytes: size,
+                path: path.to_path_buf(),
+                modified_at: modified
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs()),
+            });
+        }
+    }
+
+    /// Returns the size and count added since the last call, once enough files have piled up.
+    fn take_unreported(&mut self) -> Option<(u64, u64)> {
+        if self.file_count - self.reported_count < PROGRESS_INTERVAL {
+            return None;
+        }
+
+        let delta = (
+            self.size_bytes - self.reported_size,
+            self.file_count - self.reported_count,
+        );
+        self.reported_size = self.size_bytes;
+        self.reported_count = self.file_count;
+        Some(delta)
+    }
+
+    fn merge(&mut self, other: Tally) {
+        self.size_bytes += other.size_bytes;
+        self.file_count += other.file_count;
+        self.logical_bytes += other.logical_bytes;
+        self.allocated_bytes += other.allocated_bytes;
+        for (dir, (size, count)) in other.dirs {
+            add_to(&mut self.dirs, dir, size, count);
+        }
+        self.largest.merge(other.largest);
+        for (ext, (size, count)) in other.extensions {
+            add_to(&mut self.extensions, ext, size, count);
+        }
+        for (total, (size, count)) in self.ages.iter_mut().zip(other.ages) {
+            total.0 += size;
+            total.1 += count;
+        }
+        self.stale_bytes += other.stale_bytes;
+        self.hardlink_bytes_saved += other.hardlink_bytes_saved;
+        self.snapshot_dirs.extend(other.snapshot_dirs);
+        self.reused_dirs += other.reused_dirs;
+    }
+
+    fn into_summary(self, ctx: &RunContext, cancelled: bool) -> ScanSummary {
+        let scanner = ctx.scanner;
+        let mut summary = ScanSummary {
+            size_bytes: self.size_bytes,
+            file_count: self.file_count,
+            logical_bytes: self.logical_bytes,
+            allocated_bytes: self.allocated_bytes,
+            cancelled,
+            reused_dirs: self.reused_dirs,
+            ..Default::default()
+        };
+
+        if ctx.recording {
+            let mut snapshot = Snapshot {
+                taken_at: utils::now_secs(),
+                options: scanner.snapshot_options(),
+                dirs: self.snapshot_dirs,
+            };
+            snapshot.link_subdirs();
+            summary.snapshot = Some(snapshot);
+        }
+
+        if scanner.breakdown_depth.is_some() {
+            let mut tree = NodeBuilder::default();
+            for (dir, (size, count)) in self.dirs {
+                tree.add(&dir, size, count);
+            }
+            summary.breakdown = Some(tree.build(&scanner.root));
+        }
+        if scanner.largest_files > 0 {
+            summary.largest_files = Some(self.largest.into_sorted());
+        }
+        if scanner.extension_stats {
+            summary.extensions = Some(bucket_extensions(self.extensions));
+        }
+        if scanner.age_histogram {
+            summary.age_buckets = Some(
+                AGE_BUCKETS
+                    .iter()
+                    .zip(self.ages)
+                    .map(|((label, max), (size_bytes, file_count))| AgeBucket {
+                        label: label.to_string(),
+                        max_age_days: max.map(|secs| secs / DAY_SECS),
+                        size_bytes,
+                        file_count,
+                    })
+                    .collect(),
+            );
+        }
+        if scanner.stale_after_days.is_some() {
+            summary.stale_bytes = Some(self.stale_bytes);
+        }
+        if scanner.dedupe_hardlinks {
+            summary.hardlink_bytes_saved = Some(self.hardlink_bytes_saved);
+        }
+
+        summary
+    }
+}
+
+/// A file competing for the largest-files report, ordered by size.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Candidate {
+    size_bytes: u64,
+    path: PathBuf,
+    modified_at: Option<u64>,
+}
+
+/// Min-heap holding at most `limit` of the largest files seen so far.
+#[derive(Default)]
+struct TopFiles {
+    limit: usize,
+    heap: BinaryHeap<Reverse<Candidate>>,
+}
+
+impl TopFiles {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            heap: BinaryHeap::with_capacity(limit + 1),
+        }
+    }
+
+    /// Cheap pre-check so paths are only copied for files that make the cut.
+    fn admits(&self, size_bytes: u64) -> bool {
+        self.heap.len() < self.limit
+            || self
+                .heap
+                .peek()
+                .is_some_and(|Reverse(smallest)| size_bytes > smallest.size_bytes)
+    }
+
+    fn push(&mut self, candidate: Candidate) {
+        if !self.admits(candidate.size_bytes) {
+            return;
+        }
+        if self.heap.len() >= self.limit {
+            self.heap.pop();
+        }
+        self.heap.push(Reverse(candidate));
+    }
+
+    fn merge(&mut self, other: TopFiles) {
+        for Reverse(candidate) in other.heap {
+            self.push(candidate);
+        }
+    }
+
+    fn into_sorted(self) -> Vec<LargeFile> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(c)| LargeFile {
+                path: c.path.to_string_lossy().to_string(),
+                size_bytes: c.size_bytes,
+                modified_at: c.modified_at,
+            })
+            .collect()
+    }
+}
+
+/// Mutable tree used to fold the per-directory tallies into a `SizeNode`.
+#[derive(Default)]
+struct NodeBuilder {
+    size_bytes: u64,
+    file_count: u64,
+    children: HashMap<OsString, NodeBuilder>,
+}
+
+impl NodeBuilder {
+    fn add(&mut self, dir: &Path, size_bytes: u64, file_count: u64) {
+        let mut node = self;
+        node.size_bytes += size_bytes;
+        node.file_count += file_count;
+        for component in dir.components() {
+            node = node
+                .children
+                .entry(component.as_os_str().to_os_string())
+                .or_default();
+            node.size_bytes += size_bytes;
+            node.file_count += file_count;
+        }
+    }
+
+    fn build(self, path: &Path) -> SizeNode {
+        let mut children: Vec<SizeNode> = self
+            .children
+            .into_iter()
+            .map(|(name, child)| child.build(&path.join(name)))
+            .collect();
+        children.sort_by_key(|c| Reverse(c.size_bytes));
+
+        SizeNode {
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string_lossy().to_string()),
+            path: path.to_string_lossy().to_string(),
+            size_bytes: self.size_bytes,
+            file_count: self.file_count,
+            children,
+        }
+    }
+}
+
+/// Walks a monitored folder and totals its files.
+/// Shared by manual, streaming and background scans so they always agree.
+pub struct Scanner {
+    root: PathBuf,
+    max_depth: Option<usize>,
+    workers: usize,
+    cancel: CancelToken,
+    breakdown_depth: Option<usize>,
+    largest_files: usize,
+    extension_stats: bool,
+    age_histogram: bool,
+    stale_after_days: Option<u32>,
+    size_mode: SizeMode,
+    dedupe_hardlinks: bool,
+    follow_symlinks: bool,
+    same_filesystem: bool,
+    exclude: Vec<String>,
+    include: Vec<String>,
+    previous: Option<Snapshot>,
+    record_snapshot: bool,
+}
+
+impl Scanner {
+    /// `path` may contain `%VAR%` environment variables.
+    pub fn new(path: &str) -> Self {
+        Self {
+            root: PathBuf::from(utils::expand_env_vars(path)),
+            max_depth: None,
+            workers: 0,
+            cancel: CancelToken::default(),
+            breakdown_depth: None,
+            largest_files: 0,
+            extension_stats: false,
+            age_histogram: false,
+            stale_after_days: None,
+            size_mode: SizeMode::Logical,
+            dedupe_hardlinks: false,
+            follow_symlinks: false,
+            same_filesystem: false,
+            exclude: Vec::new(),
+            include: Vec::new(),
+            previous: None,
+            record_snapshot: false,
+        }
+    }
+
+    /// A scanner for the monitor's path with all of its scan options.
+    pub fn for_monitor(monitor: &MonitorConfig) -> Self {
+        Self::new(&monitor.path)
+            .max_depth(monitor.max_depth)
+            .monitor_options(monitor)
+    }
+
+    /// Applies the monitor's scan options, leaving path and depth alone.
+    pub fn monitor_options(self, monitor: &MonitorConfig) -> Self {
+        self.walk_options(monitor)
+            .largest_files(monitor.largest_files_limit.unwrap_or(0))
+            .extension_stats(monitor.extension_stats)
+            .age_histogram(monitor.age_histogram)
+            .stale_after_days(monitor.stale_after_days)
+            .dedupe_hardlinks(monitor.dedupe_hardlinks)
+    }
+
+    /// Applies only the options that decide which files are counted and how,
+    /// leaving out the extra statistics.
+    pub fn walk_options(self, monitor: &MonitorConfig) -> Self {
+        self.size_mode(monitor.size_mode)
+            .follow_symlinks(monitor.follow_symlinks)
+            .same_filesystem(monitor.same_filesystem)
+            .exclude(monitor.exclude.clone())
+            .include(monitor.include.clone())
+    }
+
+    /// `None` or `0` scans the whole tree.
+    pub fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// `0` uses one worker per CPU core.
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    pub fn cancel_token(mut self, cancel: CancelToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Also collect sizes for subdirectories down to `depth` levels below the root.
+    pub fn breakdown_depth(mut self, depth: usize) -> Self {
+        self.breakdown_depth = Some(depth.max(1));
+        self
+    }
+
+    /// Also keep the `count` largest files. `0` turns this off.
+    pub fn largest_files(mut self, count: usize) -> Self {
+        self.largest_files = count;
+        self
+    }
+
+    /// Also total bytes and files per file extension.
+    pub fn extension_stats(mut self, enabled: bool) -> Self {
+        self.extension_stats = enabled;
+        self
+    }
+
+    /// Also bucket bytes by how long ago each file was last modified.
+    pub fn age_histogram(mut self, enabled: bool) -> Self {
+        self.age_histogram = enabled;
+        self
+    }
+
+    /// Also total the bytes not modified in the last `days` days. `None` or `0` turns this off.
+    pub fn stale_after_days(mut self, days: Option<u32>) -> Self {
+        self.stale_after_days = days.filter(|d| *d > 0);
+        self
+    }
+
+    /// Which size to total. Both are always reported in the summary.
+    pub fn size_mode(mut self, mode: SizeMode) -> Self {
+        self.size_mode = mode;
+        self
+    }
+
+    /// Count a file with several hard links only once, the first time one of them is seen.
+    pub fn dedupe_hardlinks(mut self, enabled: bool) -> Self {
+        self.dedupe_hardlinks = enabled;
+        self
+    }
+
+    /// Follow symlinks and junctions instead of skipping them.
+    pub fn follow_symlinks(mut self, enabled: bool) -> Self {
+        self.follow_symlinks = enabled;
+        self
+    }
+
+    /// Stay on the root's file system instead of descending into other mounts.
+    pub fn same_filesystem(mut self, enabled: bool) -> Self {
+        self.same_filesystem = enabled;
+        self
+    }
+
+    /// Glob patterns for files and directories to leave out. See `PathFilter`.
+    pub fn exclude(mut self, patterns: Vec<String>) -> Self {
+        self.exclude = patterns;
+        self
+    }
+
+    /// Glob patterns for the only files to count. Empty counts every file.
+    pub fn include(mut self, patterns: Vec<String>) -> Self {
+        self.include = patterns;
+        self
+    }
+
+    /// Reuse totals from `previous` for directories whose mtime hasn't changed.
+    /// Ignored when the snapshot is too old, was taken with other options, or
+    /// the scan collects per-file statistics, which a snapshot doesn't keep.
+    pub fn snapshot(mut self, previous: Option<Snapshot>) -> Self {
+        self.previous = previous;
+        self
+    }
+
+    /// Also return a snapshot of this scan for the next one to build on.
+    pub fn record_snapshot(mut self, enabled: bool) -> Self {
+        self.record_snapshot = enabled;
+        self
+    }
+
+    /// Everything besides file contents that decides which directories a walk visits.
+    fn snapshot_options(&self) -> String {
+        format!(
+            "{}|{:?}|{}|{}|{:?}|{:?}",
+            self.root.display(),
+            self.max_depth.filter(|d| *d > 0),
+            self.follow_symlinks,
+            self.same_filesystem,
+            self.exclude,
+            self.include
+        )
+    }
+
+    /// Snapshots only keep per-directory totals, so these options need every file visited.
+    fn collects_per_file(&self) -> bool {
+        self.breakdown_depth.is_some()
+            || self.largest_files > 0
+            || self.extension_stats
+            || self.age_histogram
+            || self.stale_after_days.is_some()
+            || self.dedupe_hardlinks
+    }
+
+    pub fn run(&self, progress: &dyn ProgressCallback) -> Result<ScanSummary, String> {
+        if !self.root.exists() {
+            return Err("Path not found".to_string());
+        }
+        let filter = PathFilter::new(&self.exclude, &self.include)?;
+
+        let total_size = AtomicU64::new(0);
+        let total_count = AtomicU64::new(0);
+        let recording = self.record_snapshot && !self.collects_per_file();
+        let options = self.snapshot_options();
+        let previous = self.previous.as_ref().filter(|previous| {
+            recording
+                && previous.options == options
+                && utils::now_secs().saturating_sub(previous.taken_at) < MAX_SNAPSHOT_AGE_SECS
+        });
+        let ctx = RunContext {
+            scanner: self,
+            started_at: SystemTime::now(),
+            seen_links: Mutex::new(HashSet::new()),
+            previous,
+            recording,
+            cluster: cluster_size(&self.root),
+        };
+        let report = |tally: &mut Tally| {
+            if let Some((size, count)) = tally.take_unreported() {
+                progress.on_progress(
+                    total_size.fetch_add(size, Ordering::Relaxed) + size,
+                    total_count.fetch_add(count, Ordering::Relaxed) + count,
+                );
+            }
+        };
+
+        let (tallies, errors) = ParallelWalker::new(&self.root, self.max_depth, self.workers)
+            .follow_symlinks(self.follow_symlinks)
+            .same_filesystem(self.same_filesystem)
+            .filter(filter)
+            .walk(
+                &self.cancel,
+                || Tally::new(self),
+                |tally, path| {
+                    let action = tally.enter(&ctx, path);
+                    report(tally);
+                    action
+                },
+                |tally, path, metadata| {
+                    tally.record(&ctx, path, metadata);
+                    report(tally);
+                },
+            );
+
+        let mut total = Tally::new(self);
+        for tally in tallies {
+            total.merge(tally);
+        }
+        for dir in &errors.incomplete_dirs {
+            let relative = dir.strip_prefix(&self.root).unwrap_or(dir);
+            if let Some(record) = total.snapshot_dirs.get_mut(relative) {
+                record.incomplete = true;
+            }
+        }
+
+        let mut summary = total.into_summary(&ctx, self.cancel.is_cancelled());
+        summary.skipped_count = errors.skipped;
+        summary.skipped_samples = errors.samples;
+        Ok(summary)
+    }
+}
+
+/// Bytes the file occupies on disk.
+#[cfg(unix)]
+pub fn allocated_size(_path: &Path, metadata: &fs::Metadata, _cluster: u64) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    // st_blocks is always in 512-byte units, whatever the file system block size
+    metadata.blocks() * 512
+}
+
+/// Bytes the file occupies on disk, in whole clusters of `cluster` bytes.
+///
+/// Only compressed and sparse files take less than their length, so only those
+/// cost a system call; everything else is worked out from the listing.
+#[cfg(windows)]
+pub fn allocated_size(path: &Path, metadata: &fs::Metadata, cluster: u64) -> u64 {
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::fs::MetadataExt;
+    use windows_sys::Win32::Foundation::{GetLastError, NO_ERROR};
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetCompressedFileSizeW, FILE_ATTRIBUTE_COMPRESSED, FILE_ATTRIBUTE_SPARSE_FILE,
+        INVALID_FILE_SIZE,
+    };
+
+    let round_up = |bytes: u64| bytes.div_ceil(cluster) * cluster;
+    if metadata.file_attributes() & (FILE_ATTRIBUTE_COMPRESSED | FILE_ATTRIBUTE_SPARSE_FILE) == 0 {
+        return round_up(metadata.len());
+    }
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut high = 0u32;
+    // SAFETY: `wide` is NUL-terminated and outlives the call
+    let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+    if low == INVALID_FILE_SIZE && unsafe { GetLastError() } != NO_ERROR {
+        return round_up(metadata.len());
+    }
+    round_up(((high as u64) << 32) | low as u64)
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn allocated_size(_path: &Path, metadata: &fs::Metadata, _cluster: u64) -> u64 {
+    metadata.len()
+}
+
+/// Bytes per cluster on the volume holding `root`, the unit files are
+/// allocated in. Looked up once per scan rather than per file.
+#[cfg(windows)]
+pub fn cluster_size(root: &Path) -> u64 {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{GetDiskFreeSpaceW, GetVolumePathNameW};
+
+    let wide: Vec<u16> = root.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut volume = [0u16; 1024];
+    let (mut sectors, mut bytes, mut free, mut total) = (0u32, 0u32, 0u32, 0u32);
+    // SAFETY: both buffers are NUL-terminated and outlive the calls, and the
+    // volume buffer's length is passed along with it
+    let found = unsafe {
+        GetVolumePathNameW(wide.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) != 0
+            && GetDiskFreeSpaceW(
+                volume.as_ptr(),
+                &mut sectors,
+                &mut bytes,
+                &mut free,
+                &mut total,
+            ) != 0
+    };
+    match found {
+        true => (sectors as u64 * bytes as u64).max(1),
+        false => 1,
+    }
+}
+
+#[cfg(not(windows))]
+pub fn cluster_size(_root: &Path) -> u64 {
+    1
+}
+
+/// (device, file id) for a file with more than one hard link, `None` otherwise.
+#[cfg(unix)]
+fn hardlink_id(_path: &Path, metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+}
+
+/// (volume serial, file index) for a file with more than one hard link, `None` otherwise.
+/// Directory listings don't carry the link count on Windows, so this opens the file.
+#[cfg(windows)]
+fn hardlink_id(path: &Path, _metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION,
+    };
+
+    // No access rights needed to read the file's identity, so this works on locked files
+    let file = fs::OpenOptions::new().access_mode(0).open(path).ok()?;
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    // SAFETY: the handle stays open for the duration of the call
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle(), &mut info) } == 0 {
+        return None;
+    }
+    (info.nNumberOfLinks > 1).then(|| {
+        (
+            info.dwVolumeSerialNumber as u64,
+            ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64,
+        )
+    })
+}
+
+#[cfg(not(any(unix, windows)))]
+fn hardlink_id(_path: &Path, _metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Sorts extensions by size and folds everything past `EXTENSION_BUCKETS` into "other".
+fn bucket_extensions(extensions: HashMap<String, (u64, u64)>) -> Vec<ExtensionStat> {
+    let mut stats: Vec<ExtensionStat> = extensions
+        .into_iter()
+        .map(|(ext, (size_bytes, file_count))| ExtensionStat {
+            extension: if ext.is_empty() {
+                "(none)".to_string()
+            } else {
+                ext
+            },
+            size_bytes,
+            file_count,
+        })
+        .collect();
+    stats.sort_by_key(|s| Reverse(s.size_bytes));
+
+    if stats.len() > EXTENSION_BUCKETS {
+        let rest = stats.split_off(EXTENSION_BUCKETS);
+        stats.push(ExtensionStat {
+            extension: "other".to_string(),
+            size_bytes: rest.iter().map(|s| s.size_bytes).sum(),
+            file_count: rest.iter().map(|s| s.file_count).sum(),
+        });
+    }
+
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `files` (relative path, length) under `root`, creating parent directories.
+    fn write_files(root: &Path, files: &[(&str, usize)]) {
+        for (path, len) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, vec![b'x'; *len]).unwrap();
+        }
+    }
+
+    fn scan(scanner: Scanner) -> ScanSummary {
+        scanner.run(&|_, _| {}).unwrap()
+    }
+
+    fn scanner(root: &Path) -> Scanner {
+        Scanner::new(root.to_str().unwrap())
+    }
+
+    const TREE: &[(&str, usize)] = &[
+        ("top.txt", 100),
+        ("a/one.log", 200),
+        ("a/b/two.txt", 300),
+        ("a/b/c/three.log", 400),
+        ("skip/four.txt", 500),
+    ];
+
+    #[test]
+    fn totals_cover_every_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write_files(dir.path(), TREE);
+
+        for workers in [1, 4] {
+            let summary = scan(scanner(dir.path()).workers(workers));
+            assert_eq!(summary.size_bytes, 1500);
+            assert_eq!(summary.file_count, 5);
+            assert_eq!(summary.skipped_count, 0);
+            assert!(!summary.cancelled);
+        }
+    }
+
+    #[test]
+    fn max_depth_limits_how_far_down_files_count() {
+        let dir = tempfile::tempdir().unwrap();
+        write_files(dir.path(), TREE);
+
+        let totals = |depth| {
+            let summary = scan(scanner(dir.path()).max_depth(depth));
+            (summary.size_bytes, summary.file_count)
+        };
+        assert_eq!(totals(Some(1)), (100, 1));
+        assert_eq!(totals(Some(2)), (800, 3));
+        assert_eq!(totals(Some(3)), (1100, 4));
+        assert_eq!(totals(None), (1500, 5));
+    }
+
+    #[test]
+    fn cancelled_scans_are_flagged() {
+        let dir = tempfile::tempdir().unwrap();
+        write_files(dir.path(), TREE);
+
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        let summary = scan(scanner(dir.path()).cancel_token(cancel));
+        assert!(summary.cancelled);
+        assert_eq!(summary.file_count, 0);
+    }
+
+    #[test]
+    fn missing_root_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = scanner(&dir.path().join("gone")).run(&|_, _| {});
+        assert_eq!(result.unwrap_err(), "Path not found");
+    }
+
+    #[test]
+    fn exclude_and_include_filter_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write_files(dir.path(), TREE);
+
+        let summary = scan(scanner(dir.path()).exclude(vec!["skip".into(), "b".into()]));
+        assert_eq!((summary.size_bytes, summary.file_count), (300, 2));
+
+        let summary = scan(scanner(dir.path()).include(vec!["**/*.log".into()]));
+        assert_eq!((summary.size_bytes, summary.file_count), (600, 2));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn followed_symlink_loops_count_once() {
+        let dir = tempfile::tempdir().unwrap();
+        write_files(dir.path(), TREE);
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("a/b/up")).unwrap();
+
+        let summary = scan(scanner(dir.path()).follow_symlinks(true));
+        assert_eq!((summary.size_bytes, summary.file_count), (1500, 5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_links_are_counted_once_when_deduped() {
+        let dir = tempfile::tempdir().unwrap();
+        write_files(dir.path(), TREE);
+        fs::hard_link(
+            dir.path().join("a/one.log"),
+            dir.path().join("skip/link.log"),
+        )
+        .unwrap();
+
+        let summary = scan(scanner(dir.path()));
+        assert_eq!((summary.size_bytes, summary.file_count), (1700, 6));
+        assert_eq!(summary.hardlink_bytes_saved, None);
+
+        let summary = scan(scanner(dir.path()).dedupe_hardlinks(true));
+        assert_eq!(summary.size_bytes, 1500);
+        assert_eq!(summary.hardlink_bytes_saved, Some(200));
+    }
+
+    #[test]
+    fn incremental_scans_reuse_unchanged_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        write_files(dir.path(), TREE);
+        let incremental = || scanner(dir.path()).record_snapshot(true);
+
+        let first = scan(incremental());
+        assert_eq!(first.reused_dirs, 0);
+        let second = scan(incremental().snapshot(first.snapshot.clone()));
+        assert_eq!((second.size_bytes, second.file_count), (1500, 5));
+        assert_eq!(second.reused_dirs, 5);
+
+        // A directory that had unreadable entries is listed again
+        let mut snapshot = first.snapshot.unwrap();
+        snapshot.dirs.get_mut(Path::new("a/b")).unwrap().incomplete = true;
+        let third = scan(incremental().snapshot(Some(snapshot)));
+        assert_eq!((third.size_bytes, third.file_count), (1500, 5));
+        assert_eq!(third.reused_dirs, 4);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::types::{AppSettings, MonitorConfig};
+use chrono::{Local, TimeZone};
+use cron::Schedule;
+use std::str::FromStr;
+
+/// Parses a cron expression. Standard five-field expressions
+/// (minute hour day month weekday) are accepted as well as the six- and
+/// seven-field forms with seconds and year.
+pub fn parse(expression: &str) -> Result<Schedule, String> {
+    let expression = expression.trim();
+    let expression = if expression.split_whitespace().count() == 5 {
+        format!("0 {}", expression)
+    } else {
+        expression.to_string()
+    };
+
+    Schedule::from_str(&expression).map_err(|e| format!("Invalid schedule: {}", e))
+}
+
+/// First time after `after` (Unix seconds) that `expression` fires, in local time.
+pub fn next_cron_run(expression: &str, after: u64) -> Result<u64, String> {
+    let schedule = parse(expression)?;
+    let after = Local
+        .timestamp_opt(after as i64, 0)
+        .single()
+        .ok_or_else(|| "Invalid time".to_string())?;
+
+    schedule
+        .after(&after)
+        .next()
+        .map(|t| t.timestamp().max(0) as u64)
+        .ok_or_else(|| "Schedule never fires".to_string())
+}
+
+/// Whether the monitor is scanned on the global check interval, having
+/// neither a valid schedule nor an interval of its own.
+pub fn uses_global_interval(monitor: &MonitorConfig) -> bool {
+    let scheduled = monitor
+        .schedule
+        .as_deref()
+        .filter(|s| !s.trim().is_empty())
+        .is_some_and(|s| parse(s).is_ok());
+    !scheduled && monitor.interval_minutes.filter(|m| *m > 0).is_none()
+}
+
+/// When a monitor scanned at `after` is next due: its cron schedule if it has
+/// a valid one, otherwise its own interval, otherwise the global interval.
+pub fn next_scan_at(monitor: &MonitorConfig, settings: &AppSettings, after: u64) -> u64 {
+    if let Some(expression) = monitor.schedule.as_deref().filter(|s| !s.trim().is_empty()) {
+        if let Ok(next) = next_cron_run(expression, after) {
+            return next;
+        }
+    }
+
+    let minutes = monitor
+        .interval_minutes
+        .filter(|m| *m > 0)
+        .unwrap_or(settings.check_interval_minutes)
+        .max(1);
+    after + minutes as u64 * 60
+}
+
+/// The monitor's saved due time, or one worked out from its last scan for
+/// monitors that haven't been through the scheduler yet.
+pub fn due_at(monitor: &MonitorConfig, settings: &AppSettings) -> u64 {
+    match (monitor.next_scan_at, monitor.last_scan_at) {
+        (Some(next), _) => next,
+        (None, Some(last)) => next_scan_at(monitor, settings, last),
+        (None, None) => 0,
+    }
+}
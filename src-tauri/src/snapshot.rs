@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Snapshots older than this are ignored so files that changed in place,
+/// which doesn't touch their directory's mtime, are picked up at least daily.
+pub const MAX_SNAPSHOT_AGE_SECS: u64 = 24 * 60 * 60;
+
+/// What a previous scan found in each directory of a monitor, so the next scan
+/// can skip listing directories whose mtime hasn't changed.
+///
+/// A directory's mtime only changes when entries are added, removed or renamed
+/// in it, so a file that grows in place is missed until the next full scan.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub taken_at: u64,
+    /// Describes the scan options the snapshot was taken with. A snapshot is
+    /// only reused by a scan with the same options.
+    pub options: String,
+    /// Keyed by path relative to the scan root, empty for the root itself.
+    pub dirs: HashMap<PathBuf, DirRecord>,
+}
+
+/// Files directly inside one directory, not counting subdirectories.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirRecord {
+    pub mtime_ns: u64,
+    pub logical_bytes: u64,
+    pub allocated_bytes: u64,
+    pub file_count: u64,
+    /// Names of the subdirectories the scan descended into.
+    pub subdirs: Vec<String>,
+    /// The listing failed or missed entries, so the totals above are short
+    /// and the directory is listed again rather than reused.
+    #[serde(default)]
+    pub incomplete: bool,
+}
+
+impl Snapshot {
+    /// The record for `relative` if its directory hasn't changed since.
+    pub fn unchanged(&self, relative: &Path, mtime_ns: u64) -> Option<&DirRecord> {
+        self.dirs
+            .get(relative)
+            .filter(|record| record.mtime_ns == mtime_ns && !record.incomplete)
+    }
+
+    /// Fills in each directory's subdirectory list from the set of directories walked.
+    pub fn link_subdirs(&mut self) {
+        let children: Vec<(PathBuf, String)> = self
+            .dirs
+            .keys()
+            .filter_map(|dir| {
+                let name = dir.file_name()?.to_string_lossy().to_string();
+                Some((dir.parent()?.to_path_buf(), name))
+            })
+            .collect();
+
+        for record in self.dirs.values_mut() {
+            record.subdirs.clear();
+        }
+        for (parent, name) in children {
+            if let Some(record) = self.dirs.get_mut(&parent) {
+                record.subdirs.push(name);
+            }
+        }
+    }
+}
+
+/// Last-modified time in nanoseconds, `None` if it can't be read.
+pub fn dir_mtime_ns(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64)
+}
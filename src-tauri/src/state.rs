@@ -0,0 +1,278 @@
+use crate::error::MistError;
+use crate::types::{Alert, AlertLevel, AppSettings, MonitorConfig, ThresholdLevel};
+use crate::watcher::WatchRegistry;
+use crate::{alerts, schedule, storage, utils};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager};
+
+/// Monitors and settings shared by commands, the background worker and toast
+/// callbacks.
+///
+/// The JSON files are read once at startup. Every change is written through to
+/// disk while the lock is held, so writes land in the order they were made and
+/// a read-modify-write from one thread can't undo another's.
+#[derive(Clone)]
+pub struct AppState {
+    inner: Arc<Mutex<Inner>>,
+}
+
+struct Inner {
+    monitors: Vec<MonitorConfig>,
+    settings: AppSettings,
+    /// Problems found while loading, shown by the UI once it is up.
+    warnings: Vec<String>,
+    /// Threshold alert of each monitor, as of its last scan or live update.
+    alerts: HashMap<String, Alert>,
+}
+
+impl AppState {
+    pub fn load(app_handle: &tauri::AppHandle) -> Self {
+        let monitors = storage::load_monitors_from_file(app_handle);
+        let settings = storage::load_settings(app_handle);
+        let warnings = [monitors.warning, settings.warning]
+            .into_iter()
+            .flatten()
+            .collect();
+
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                monitors: monitors.value,
+                settings: settings.value,
+                warnings,
+                alerts: storage::load_alerts(app_handle),
+            })),
+        }
+    }
+
+    /// Loading problems not yet shown to the user.
+    pub fn take_warnings(&self) -> Vec<String> {
+        std::mem::take(&mut self.inner.lock().unwrap().warnings)
+    }
+
+    pub fn settings(&self) -> AppSettings {
+        self.inner.lock().unwrap().settings.clone()
+    }
+
+    pub fn monitors(&self) -> Vec<MonitorConfig> {
+        self.inner.lock().unwrap().monitors.clone()
+    }
+
+    pub fn monitor(&self, monitor_id: &str) -> Option<MonitorConfig> {
+        let inner = self.inner.lock().unwrap();
+        inner.monitors.iter().find(|m| m.id == monitor_id).cloned()
+    }
+
+    pub fn set_settings(
+        &self,
+        app_handle: &tauri::AppHandle,
+        settings: AppSettings,
+    ) -> Result<(), MistError> {
+        let mut inner = self.inner.lock().unwrap();
+        let saved = storage::save_settings_to_file(app_handle, &settings);
+        let interval_changed =
+            inner.settings.check_interval_minutes != settings.check_interval_minutes;
+        inner.settings = settings;
+
+        // Due times worked out from the old interval; `due_at` takes the new
+        // one from the last scan instead
+        let mut rescheduled = false;
+        if interval_changed {
+            for monitor in inner.monitors.iter_mut() {
+                if schedule::uses_global_interval(monitor) {
+                    rescheduled |= monitor.next_scan_at.take().is_some();
+                }
+            }
+        }
+        if rescheduled {
+            if let Err(e) = storage::save_monitors_to_file(app_handle, &inner.monitors) {
+                app_handle.emit("storage-error", e).ok();
+            }
+        }
+        drop(inner);
+
+        app_handle.emit("settings-updated", ()).ok();
+        if rescheduled {
+            app_handle.emit("monitors-updated", ()).ok();
+        }
+        saved
+    }
+
+    /// Replaces the monitor list with one edited in the UI.
+    ///
+    /// The UI's copy may predate a background scan that finished meanwhile, so
+    /// scan times are taken from whichever side is newer, and the next due time
+    /// is kept unless the monitor's timing was changed. A copy from before a
+    /// toast muted the monitor keeps it muted.
+    pub fn set_monitors(
+        &self,
+        app_handle: &tauri::AppHandle,
+        mut monitors: Vec<MonitorConfig>,
+    ) -> Result<(), MistError> {
+        for monitor in &monitors {
+            storage::check_monitor_id(&monitor.id)?;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        for monitor in monitors.iter_mut() {
+            let Some(current) = inner.monitors.iter().find(|m| m.id == monitor.id) else {
+                continue;
+            };
+            monitor.last_scan_at = monitor.last_scan_at.max(current.last_scan_at);
+            if current.muted_at > monitor.muted_at {
+                monitor.notify = false;
+                monitor.muted_at = current.muted_at;
+            }
+            if monitor.interval_minutes == current.interval_minutes
+                && monitor.schedule == current.schedule
+            {
+                monitor.next_scan_at = current.next_scan_at;
+            }
+        }
+
+        let saved = storage::save_monitors_to_file(app_handle, &monitors);
+        let previous = std::mem::replace(&mut inner.monitors, monitors.clone());
+        // A failed save leaves the removed monitors on disk, so keep their data too
+        if saved.is_ok() {
+            Self::forget_removed(app_handle, &mut inner, &previous);
+        }
+        drop(inner);
+        app_handle
+            .state::<WatchRegistry>()
+            .sync(app_handle, &monitors);
+        saved
+    }
+
+    pub fn alerts(&self) -> HashMap<String, Alert> {
+        self.inner.lock().unwrap().alerts.clone()
+    }
+
+    /// Feeds a monitor's new size through its threshold alert and sums up
+    /// every monitor's level in the tray tooltip.
+    /// Returns the monitor's level, and the level to notify about if any.
+    pub fn update_alert(
+        &self,
+        app_handle: &tauri::AppHandle,
+        monitor: &MonitorConfig,
+        mb: f64,
+    ) -> (AlertLevel, Option<ThresholdLevel>) {
+        let result = self.check_alert(app_handle, &monitor.id, |alert, settings| {
+            let notify = alerts::update(alert, monitor, settings, mb, utils::now_secs());
+            (alert.level, notify)
+        });
+        self.update_tray(app_handle);
+        result
+    }
+
+    /// Moves a monitor's alert on with one of the `alerts` updates, saving
+    /// and publishing it if that changed anything.
+    pub fn check_alert<R>(
+        &self,
+        app_handle: &tauri::AppHandle,
+        monitor_id: &str,
+        update: impl FnOnce(&mut Alert, &AppSettings) -> R,
+    ) -> R {
+        let mut inner = self.inner.lock().unwrap();
+        let settings = inner.settings.clone();
+        let alert = inner.alerts.entry(monitor_id.to_string()).or_default();
+        let before = alert.clone();
+        let result = update(alert, &settings);
+
+        if *alert != before {
+            Self::publish_alerts(app_handle, &inner);
+        }
+        result
+    }
+
+    /// Changes a monitor's alert from the UI or a toast action.
+    pub fn change_alert(
+        &self,
+        app_handle: &tauri::AppHandle,
+        monitor_id: &str,
+        change: impl FnOnce(&mut Alert),
+    ) -> Result<(), MistError> {
+        let mut inner = self.inner.lock().unwrap();
+        change(inner.alerts.entry(monitor_id.to_string()).or_default());
+        let saved = storage::save_alerts(app_handle, &inner.alerts);
+        app_handle.emit("alerts-updated", &inner.alerts).ok();
+        saved
+    }
+
+    /// Saves alerts changed in the background and tells the UI.
+    fn publish_alerts(app_handle: &tauri::AppHandle, inner: &Inner) {
+        if let Err(e) = storage::save_alerts(app_handle, &inner.alerts) {
+            app_handle.emit("storage-error", e).ok();
+        }
+        app_handle.emit("alerts-updated", &inner.alerts).ok();
+    }
+
+    /// Drops the alert, history and snapshot of each monitor in `previous` that
+    /// is no longer in `inner.monitors`.
+    fn forget_removed(
+        app_handle: &tauri::AppHandle,
+        inner: &mut Inner,
+        previous: &[MonitorConfig],
+    ) {
+        let kept = |id: &str| inner.monitors.iter().any(|m| m.id == id);
+        let removed: Vec<&str> = previous
+            .iter()
+            .map(|m| m.id.as_str())
+            .filter(|id| !kept(id))
+            .collect();
+        for id in &removed {
+            storage::remove_monitor_data(app_handle, id);
+        }
+        inner.alerts.retain(|id, _| !removed.contains(&id.as_str()));
+    }
+
+    /// Sums up every monitor's threshold level in the tray tooltip.
+    fn update_tray(&self, app_handle: &tauri::AppHandle) {
+        let inner = self.inner.lock().unwrap();
+        let (mut warning, mut critical) = (0, 0);
+        for monitor in inner.monitors.iter().filter(|m| m.enabled) {
+            match inner.alerts.get(&monitor.id).map(|a| a.level) {
+                Some(AlertLevel::Warning) => warning += 1,
+                Some(AlertLevel::Critical) => critical += 1,
+                _ => {}
+            }
+        }
+
+        let tooltip = match (critical, warning) {
+            (0, 0) => "Mist: all folders within limits".to_string(),
+            (0, w) => format!("Mist: {} near limit", w),
+            (c, 0) => format!("Mist: {} over limit", c),
+            (c, w) => format!("Mist: {} over limit, {} near limit", c, w),
+        };
+        // Setting it waits on the main thread, which may be waiting on this lock
+        drop(inner);
+        if let Some(tray) = app_handle.tray_by_id("main") {
+            tray.set_tooltip(Some(tooltip)).ok();
+        }
+    }
+
+    /// Changes monitors in place and tells the UI to reload them.
+    /// A failed save is reported to the UI as a `storage-error` event, as
+    /// these changes come from the background rather than a command.
+    pub fn update_monitors<R>(
+        &self,
+        app_handle: &tauri::AppHandle,
+        update: impl FnOnce(&mut Vec<MonitorConfig>) -> R,
+    ) -> R {
+        let mut inner = self.inner.lock().unwrap();
+        let previous = inner.monitors.clone();
+        let result = update(&mut inner.monitors);
+        match storage::save_monitors_to_file(app_handle, &inner.monitors) {
+            Ok(()) => Self::forget_removed(app_handle, &mut inner, &previous),
+            Err(e) => {
+                app_handle.emit("storage-error", e).ok();
+            }
+        }
+        let monitors = inner.monitors.clone();
+        drop(inner);
+
+        app_handle
+            .state::<WatchRegistry>()
+            .sync(app_handle, &monitors);
+        app_handle.emit("monitors-updated", ()).ok();
+        result
+    }
+}
@@ -1,63 +1,671 @@
 use crate::defaults;
-use crate::{AppSettings, MonitorConfig};
+use crate::error::MistError;
+use crate::history::History;
+use crate::snapshot::Snapshot;
+use crate::{Alert, AppSettings, MonitorConfig};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
 use tauri::Manager;
 
-fn get_config_path(app_handle: &tauri::AppHandle) -> PathBuf {
+pub fn get_config_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, MistError> {
     app_handle
         .path()
         .app_config_dir()
-        .unwrap()
-        .join("monitors.json")
+        .map_err(|e| MistError::Io(format!("Config folder unavailable: {}", e)))
 }
 
-fn get_settings_path(app_handle: &tauri::AppHandle) -> PathBuf {
-    app_handle
-        .path()
-        .app_config_dir()
-        .unwrap()
-        .join("settings.json")
+fn get_config_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, MistError> {
+    Ok(get_config_dir(app_handle)?.join("monitors.json"))
+}
+
+fn get_settings_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, MistError> {
+    Ok(get_config_dir(app_handle)?.join("settings.json"))
+}
+
+fn get_snapshot_path(
+    app_handle: &tauri::AppHandle,
+    monitor_id: &str,
+) -> Result<PathBuf, MistError> {
+    monitor_file(app_handle, "snapshots", monitor_id)
+}
+
+fn get_history_path(app_handle: &tauri::AppHandle, monitor_id: &str) -> Result<PathBuf, MistError> {
+    monitor_file(app_handle, "history", monitor_id)
+}
+
+/// `<config>/<folder>/<id>.json`, for ids that can't point anywhere else.
+fn monitor_file(
+    app_handle: &tauri::AppHandle,
+    folder: &str,
+    monitor_id: &str,
+) -> Result<PathBuf, MistError> {
+    check_monitor_id(monitor_id)?;
+    Ok(get_config_dir(app_handle)?
+        .join(folder)
+        .join(format!("{}.json", monitor_id)))
+}
+
+/// Monitor ids name files of their own, so only letters, digits, `-` and
+/// `_` are allowed.
+pub fn check_monitor_id(monitor_id: &str) -> Result<(), MistError> {
+    let valid = !monitor_id.is_empty()
+        && monitor_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    match valid {
+        true => Ok(()),
+        false => Err(MistError::InvalidConfig(format!(
+            "Invalid monitor id \"{}\"",
+            monitor_id
+        ))),
+    }
+}
+
+fn get_alerts_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, MistError> {
+    Ok(get_config_dir(app_handle)?.join("alerts.json"))
+}
+
+/// Older copies kept of each file, newest first.
+const BACKUP_COUNT: usize = 5;
+
+/// A new backup is taken at most this often, so the worker saving scan times
+/// doesn't push every older copy out within a few checks.
+const BACKUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// A file's contents along with what happened while reading it.
+pub struct Loaded<T> {
+    pub value: T,
+    /// Set when the file was damaged, for the UI to show.
+    pub warning: Option<String>,
+}
+
+/// Format written by this build. Files are `{ "version": n, "<key>": data }`;
+/// version 1 is the bare data saved before files were versioned.
+const MONITORS_VERSION: u64 = 2;
+const SETTINGS_VERSION: u64 = 2;
+
+/// Why a saved file couldn't be read.
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// Written by a newer version of the app, in a format this one doesn't know.
+    Newer {
+        version: u64,
+        supported: u64,
+    },
+    Invalid(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Newer { version, supported } => write!(
+                f,
+                "Saved by a newer version of Mist (format {}, this version reads up to {})",
+                version, supported
+            ),
+            Self::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+/// Upgrades a whole file from one version to the next. Entry `i` takes
+/// version `i + 1` to `i + 2`, so each format change appends one step.
+type Migration = fn(Value) -> Result<Value, String>;
+
+const MONITORS_MIGRATIONS: &[Migration] = &[|data| Ok(json!({ "monitors": data }))];
+const SETTINGS_MIGRATIONS: &[Migration] = &[|data| Ok(json!({ "settings": data }))];
+
+pub fn load_settings(app_handle: &tauri::AppHandle) -> Loaded<AppSettings> {
+    load_json(
+        get_settings_path(app_handle),
+        |content| parse_versioned(content, "settings", SETTINGS_MIGRATIONS),
+        AppSettings::default,
+    )
+}
+
+pub fn save_settings_to_file(
+    app_handle: &tauri::AppHandle,
+    settings: &AppSettings,
+) -> Result<(), MistError> {
+    let content = json!({ "version": SETTINGS_VERSION, "settings": settings });
+    save_json(&get_settings_path(app_handle)?, &content)
 }
 
-pub fn load_settings(app_handle: &tauri::AppHandle) -> AppSettings {
-    let path = get_settings_path(app_handle);
-    if path.exists() {
-        if let Ok(content) = fs::read_to_string(path) {
-            if let Ok(settings) = serde_json::from_str(&content) {
-                return settings;
+pub fn load_monitors_from_file(app_handle: &tauri::AppHandle) -> Loaded<Vec<MonitorConfig>> {
+    load_json(
+        get_config_path(app_handle),
+        parse_monitors,
+        defaults::get_default_monitors,
+    )
+}
+
+pub fn save_monitors_to_file(
+    app_handle: &tauri::AppHandle,
+    monitors: &[MonitorConfig],
+) -> Result<(), MistError> {
+    save_json(&get_config_path(app_handle)?, &monitors_json(monitors))
+}
+
+/// Reads monitors saved by any version of the app, for loading and importing.
+pub fn parse_monitors(content: &str) -> Result<Vec<MonitorConfig>, ParseError> {
+    parse_versioned(content, "monitors", MONITORS_MIGRATIONS)
+}
+
+/// The current file format, for saving and exporting.
+pub fn monitors_json(monitors: &[MonitorConfig]) -> Value {
+    json!({ "version": MONITORS_VERSION, "monitors": monitors })
+}
+
+/// Parses a versioned file, running it through every migration after the
+/// version it was written with.
+fn parse_versioned<T: DeserializeOwned>(
+    content: &str,
+    key: &str,
+    migrations: &[Migration],
+) -> Result<T, ParseError> {
+    let invalid = |e: serde_json::Error| ParseError::Invalid(e.to_string());
+    let mut value: Value = serde_json::from_str(content).map_err(invalid)?;
+    let current = migrations.len() as u64 + 1;
+    let version = match value.get("version") {
+        Some(version) => version
+            .as_u64()
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| ParseError::Invalid("Invalid version".to_string()))?,
+        None => 1,
+    };
+    if version > current {
+        return Err(ParseError::Newer {
+            version,
+            supported: current,
+        });
+    }
+
+    for migrate in &migrations[version as usize - 1..] {
+        value = migrate(value).map_err(ParseError::Invalid)?;
+    }
+
+    let data = value
+        .get_mut(key)
+        .map(Value::take)
+        .ok_or_else(|| ParseError::Invalid(format!("Missing \"{}\"", key)))?;
+    serde_json::from_value(data).map_err(invalid)
+}
+
+/// Files that exist but couldn't be read at their last load. What's in
+/// memory for them came from a backup or the defaults, so saving it would
+/// overwrite the real contents.
+static UNREADABLE: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Reads a JSON file, falling back to its newest readable backup if it is
+/// damaged or can't be read. `default` is only used when there is no file
+/// yet, when neither the file nor any backup can be read, or when a newer
+/// version of the app wrote the file; the file itself is kept either way, and
+/// in the last two cases left exactly as it is.
+fn load_json<T>(
+    path: Result<PathBuf, MistError>,
+    parse: impl Fn(&str) -> Result<T, ParseError>,
+    default: impl FnOnce() -> T,
+) -> Loaded<T> {
+    let path = match path {
+        Ok(path) => path,
+        Err(e) => {
+            return Loaded {
+                value: default(),
+                warning: Some(e.to_string()),
             }
         }
+    };
+    let path = path.as_path();
+    let read = fs::read_to_string(path);
+    if let Ok(mut unreadable) = UNREADABLE.lock() {
+        unreadable.retain(|p| p != path);
+        if read
+            .as_ref()
+            .is_err_and(|e| e.kind() != ErrorKind::NotFound)
+        {
+            unreadable.push(path.to_path_buf());
+        }
+    }
+    let content = match read {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Loaded {
+                value: default(),
+                warning: None,
+            }
+        }
+        // The file may be fine, just locked, so it is neither moved aside nor
+        // restored over
+        Err(e) => {
+            return match newest_backup(path, &parse) {
+                Some((value, backup)) => Loaded {
+                    value,
+                    warning: Some(format!(
+                        "{} couldn't be read ({}), so its backup{} is in use. Changes aren't saved until Mist is restarted.",
+                        file_name(path),
+                        e,
+                        modified_label(&backup)
+                    )),
+                },
+                None => Loaded {
+                    value: default(),
+                    warning: Some(format!(
+                        "{} couldn't be read ({}) and no backup could be read, so defaults are in use. Changes aren't saved until Mist is restarted.",
+                        file_name(path),
+                        e
+                    )),
+                },
+            };
+        }
+    };
+    let error = match parse(&content) {
+        Ok(value) => {
+            return Loaded {
+                value,
+                warning: None,
+            }
+        }
+        Err(e @ ParseError::Newer { .. }) => {
+            return Loaded {
+                value: default(),
+                warning: Some(format!(
+                    "{} couldn't be loaded ({}), so defaults are in use until Mist is updated. The file won't be changed.",
+                    file_name(path),
+                    e
+                )),
+            }
+        }
+        Err(ParseError::Invalid(e)) => e,
+    };
+
+    let kept = path.with_extension("corrupt.json");
+    fs::rename(path, &kept).ok();
+
+    if let Some((value, backup)) = newest_backup(path, &parse) {
+        if let Ok(content) = fs::read_to_string(&backup) {
+            write_atomic(path, &content).ok();
+        }
+        return Loaded {
+            value,
+            warning: Some(format!(
+                "{} couldn't be loaded ({}) and has been restored from a backup{}. The original was kept as {}.",
+                file_name(path),
+                error,
+                modified_label(&backup),
+                file_name(&kept)
+            )),
+        };
+    }
+
+    Loaded {
+        value: default(),
+        warning: Some(format!(
+            "{} couldn't be loaded ({}) and no backup could be read, so defaults are in use. The original was kept as {}.",
+            file_name(path),
+            error,
+            file_name(&kept)
+        )),
     }
-    AppSettings::default()
 }
 
-pub fn save_settings_to_file(app_handle: &tauri::AppHandle, settings: &AppSettings) {
-    let path = get_settings_path(app_handle);
+/// The newest backup of `path` that reads and parses, and where it is.
+fn newest_backup<T>(
+    path: &Path,
+    parse: impl Fn(&str) -> Result<T, ParseError>,
+) -> Option<(T, PathBuf)> {
+    backup_paths(path).into_iter().find_map(|backup| {
+        let value = parse(&fs::read_to_string(&backup).ok()?).ok()?;
+        Some((value, backup))
+    })
+}
+
+/// Writes a JSON file through a temporary file and a rename, so a crash
+/// leaves either the old or the new contents, and rotates the old contents
+/// into the backups first. A file written by a newer version of the app is
+/// never replaced, since that would lose whatever this version can't read,
+/// and neither is one that couldn't be read at its last load.
+fn save_json(path: &Path, value: &Value) -> Result<(), MistError> {
+    let unreadable = UNREADABLE
+        .lock()
+        .map(|unreadable| unreadable.iter().any(|p| p == path))
+        .unwrap_or(false);
+    if unreadable {
+        return Err(MistError::InvalidConfig(format!(
+            "{} couldn't be read, so changes aren't saved until Mist is restarted",
+            file_name(path)
+        )));
+    }
+
+    let version = |value: &Value| value.get("version").and_then(Value::as_u64).unwrap_or(1);
+    let existing = fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok());
+    if existing.is_some_and(|existing| version(&existing) > version(value)) {
+        return Err(MistError::InvalidConfig(format!(
+            "{} was saved by a newer version of Mist, so changes aren't saved until Mist is updated",
+            file_name(path)
+        )));
+    }
+
+    rotate_backups(path);
+    let content = serde_json::to_string_pretty(value)?;
+    write_atomic(path, &content).map_err(|e| MistError::io(path.display(), e))
+}
+
+fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
     if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).ok();
+        fs::create_dir_all(parent)?;
     }
-    let content = serde_json::to_string_pretty(settings).unwrap();
-    fs::write(path, content).ok();
+
+    let temp = path.with_extension("json.tmp");
+    let mut file = fs::File::create(&temp)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&temp, path).inspect_err(|_| {
+        fs::remove_file(&temp).ok();
+    })
 }
 
-pub fn load_monitors_from_file(app_handle: &tauri::AppHandle) -> Vec<MonitorConfig> {
-    let config_path = get_config_path(app_handle);
-    if config_path.exists() {
-        if let Ok(content) = fs::read_to_string(config_path) {
-            if let Ok(monitors) = serde_json::from_str(&content) {
-                return monitors;
-            }
+/// `backups/<name>.1.json` is the newest, up to `BACKUP_COUNT`.
+fn backup_paths(path: &Path) -> Vec<PathBuf> {
+    let dir = path.parent().unwrap_or(Path::new(".")).join("backups");
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    (1..=BACKUP_COUNT)
+        .map(|n| dir.join(format!("{}.{}.json", stem, n)))
+        .collect()
+}
+
+/// Shifts each backup down one slot and copies the current file into the
+/// first, unless the newest backup is under `BACKUP_INTERVAL` old. A file that
+/// doesn't parse isn't worth keeping and is left out.
+fn rotate_backups(path: &Path) {
+    let backups = backup_paths(path);
+    let recent = fs::metadata(&backups[0])
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.elapsed().ok())
+        .is_some_and(|age| age < BACKUP_INTERVAL);
+    if recent {
+        return;
+    }
+
+    let Ok(content) = fs::read_to_string(path) else {
+        return;
+    };
+    if serde_json::from_str::<Value>(&content).is_err() {
+        return;
+    }
+
+    if let Some(dir) = backups[0].parent() {
+        if fs::create_dir_all(dir).is_err() {
+            return;
         }
     }
-    defaults::get_default_monitors()
+    for pair in backups.windows(2).rev() {
+        if pair[0].exists() {
+            fs::rename(&pair[0], &pair[1]).ok();
+        }
+    }
+    write_atomic(&backups[0], &content).ok();
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+fn modified_label(path: &Path) -> String {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(|t| {
+            let time: chrono::DateTime<chrono::Local> = t.into();
+            format!(" saved {}", time.format("%Y-%m-%d %H:%M"))
+        })
+        .unwrap_or_default()
+}
+
+pub fn load_snapshot(app_handle: &tauri::AppHandle, monitor_id: &str) -> Option<Snapshot> {
+    let content = fs::read_to_string(get_snapshot_path(app_handle, monitor_id).ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub fn save_snapshot(app_handle: &tauri::AppHandle, monitor_id: &str, snapshot: &Snapshot) {
+    let Ok(path) = get_snapshot_path(app_handle, monitor_id) else {
+        return;
+    };
+    // Not pretty-printed, these can hold a line per directory
+    if let Ok(content) = serde_json::to_string(snapshot) {
+        write_atomic(&path, &content).ok();
+    }
+}
+
+pub fn load_history(app_handle: &tauri::AppHandle, monitor_id: &str) -> History {
+    get_history_path(app_handle, monitor_id)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_history(
+    app_handle: &tauri::AppHandle,
+    monitor_id: &str,
+    history: &History,
+) -> Result<(), MistError> {
+    let path = get_history_path(app_handle, monitor_id)?;
+    let content = serde_json::to_string(history)?;
+    write_atomic(&path, &content).map_err(|e| MistError::io(path.display(), e))
+}
+
+/// Deletes the history and snapshot of a monitor that was removed.
+pub fn remove_monitor_data(app_handle: &tauri::AppHandle, monitor_id: &str) {
+    let paths = [
+        get_history_path(app_handle, monitor_id),
+        get_snapshot_path(app_handle, monitor_id),
+    ];
+    for path in paths.into_iter().flatten() {
+        fs::remove_file(path).ok();
+    }
+}
+
+/// Alert state by monitor id. Lost or unreadable state only means alerts
+/// still firing are reported once more.
+pub fn load_alerts(app_handle: &tauri::AppHandle) -> HashMap<String, Alert> {
+    get_alerts_path(app_handle)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
 }
 
-pub fn save_monitors_to_file(app_handle: &tauri::AppHandle, monitors: &Vec<MonitorConfig>) {
-    let config_path = get_config_path(app_handle);
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent).ok();
+pub fn save_alerts(
+    app_handle: &tauri::AppHandle,
+    alerts: &HashMap<String, Alert>,
+) -> Result<(), MistError> {
+    let path = get_alerts_path(app_handle)?;
+    let content = serde_json::to_string_pretty(alerts)?;
+    write_atomic(&path, &content).map_err(|e| MistError::io(path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SizeMode;
+
+    fn parse_settings(content: &str) -> Result<AppSettings, ParseError> {
+        parse_versioned(content, "settings", SETTINGS_MIGRATIONS)
+    }
+
+    #[test]
+    fn loads_monitors_from_every_version() {
+        for content in [
+            include_str!("../tests/fixtures/monitors.v1.json"),
+            include_str!("../tests/fixtures/monitors.v2.json"),
+        ] {
+            let monitors = parse_monitors(content).unwrap();
+            assert_eq!(monitors.len(), 2);
+            assert_eq!(monitors[0].id, "def_1");
+            assert_eq!(monitors[0].last_scan_at, Some(1700000000));
+            assert_eq!(monitors[1].name, "Downloads");
+            assert_eq!(monitors[1].max_depth, Some(3));
+            assert!(!monitors[1].enabled && monitors[1].notify);
+        }
+
+        let v2 = parse_monitors(include_str!("../tests/fixtures/monitors.v2.json")).unwrap();
+        assert_eq!(v2[0].size_mode, SizeMode::Allocated);
+        assert_eq!(v2[0].exclude, ["**/*.tmp", "cache"]);
+        assert_eq!(v2[0].interval_minutes, Some(15));
+        assert_eq!(v2[1].schedule.as_deref(), Some("0 9 * * 1-5"));
+    }
+
+    #[test]
+    fn monitor_ids_cant_leave_their_folder() {
+        for id in ["def_1", "k3j9x2a", "a-b"] {
+            assert!(check_monitor_id(id).is_ok());
+        }
+        for id in ["", "../monitors", "a/b", "a\\b", "..", "C:x"] {
+            assert!(check_monitor_id(id).is_err());
+        }
+    }
+
+    #[test]
+    fn loads_settings_from_every_version() {
+        let v1 = parse_settings(include_str!("../tests/fixtures/settings.v1.json")).unwrap();
+        assert!(!v1.minimize_to_tray);
+        assert_eq!(v1.check_interval_minutes, 30);
+        assert_eq!(v1.scan_workers, 0);
+
+        let v2 = parse_settings(include_str!("../tests/fixtures/settings.v2.json")).unwrap();
+        assert!(v2.minimize_to_tray);
+        assert_eq!(v2.check_interval_minutes, 15);
+        assert_eq!(v2.scan_workers, 4);
+    }
+
+    #[test]
+    fn files_from_a_newer_version_are_left_alone() {
+        let content = r#"{ "version": 99, "monitors": [] }"#;
+        assert_eq!(
+            parse_monitors(content).unwrap_err(),
+            ParseError::Newer {
+                version: 99,
+                supported: MONITORS_VERSION
+            }
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("monitors.json");
+        fs::write(&path, content).unwrap();
+        let backups = backup_paths(&path);
+        fs::create_dir_all(backups[0].parent().unwrap()).unwrap();
+        fs::write(
+            &backups[0],
+            include_str!("../tests/fixtures/monitors.v2.json"),
+        )
+        .unwrap();
+
+        let loaded = load_json(Ok(path.clone()), parse_monitors, Vec::new);
+        assert!(loaded.value.is_empty());
+        assert!(loaded.warning.unwrap().contains("newer version"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        assert!(!path.with_extension("corrupt.json").exists());
+
+        let saved = save_json(&path, &monitors_json(&[]));
+        assert!(matches!(saved, Err(MistError::InvalidConfig(_))));
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+    }
+
+    #[test]
+    fn damaged_files_are_restored_from_a_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("monitors.json");
+        fs::write(&path, "{ not json").unwrap();
+        let backups = backup_paths(&path);
+        fs::create_dir_all(backups[0].parent().unwrap()).unwrap();
+        fs::write(
+            &backups[0],
+            include_str!("../tests/fixtures/monitors.v1.json"),
+        )
+        .unwrap();
+
+        let loaded = load_json(Ok(path.clone()), parse_monitors, Vec::new);
+        assert_eq!(loaded.value.len(), 2);
+        assert!(loaded.warning.is_some());
+        assert_eq!(
+            fs::read_to_string(path.with_extension("corrupt.json")).unwrap(),
+            "{ not json"
+        );
+        assert_eq!(
+            parse_monitors(&fs::read_to_string(&path).unwrap())
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn unreadable_files_are_not_saved_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("monitors.json");
+        // Reading a directory fails with something other than not found
+        fs::create_dir(&path).unwrap();
+        let backups = backup_paths(&path);
+        fs::create_dir_all(backups[0].parent().unwrap()).unwrap();
+        fs::write(
+            &backups[0],
+            include_str!("../tests/fixtures/monitors.v1.json"),
+        )
+        .unwrap();
+
+        let loaded = load_json(Ok(path.clone()), parse_monitors, Vec::new);
+        assert_eq!(loaded.value.len(), 2);
+        assert!(loaded.warning.unwrap().contains("backup"));
+        let saved = save_json(&path, &monitors_json(&[]));
+        assert!(matches!(saved, Err(MistError::InvalidConfig(_))));
+        assert!(path.is_dir());
+
+        // Saving is allowed again once the file loads
+        fs::remove_dir(&path).unwrap();
+        fs::write(&path, include_str!("../tests/fixtures/monitors.v2.json")).unwrap();
+        let loaded = load_json(Ok(path.clone()), parse_monitors, Vec::new);
+        assert!(loaded.warning.is_none());
+        save_json(&path, &monitors_json(&loaded.value)).unwrap();
+    }
+
+    #[test]
+    fn backups_are_taken_at_most_once_an_hour() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("monitors.json");
+        for n in 1..=4 {
+            save_json(&path, &json!({ "save": n })).unwrap();
+        }
+
+        let backups = backup_paths(&path);
+        let first: Value = serde_json::from_str(&fs::read_to_string(&backups[0]).unwrap()).unwrap();
+        assert_eq!(first, json!({ "save": 1 }));
+        assert!(!backups[1].exists());
+
+        // Once the newest backup is old enough, the next save rotates again
+        let old = std::time::SystemTime::now() - BACKUP_INTERVAL;
+        fs::File::options()
+            .write(true)
+            .open(&backups[0])
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        save_json(&path, &json!({ "save": 5 })).unwrap();
+        let first: Value = serde_json::from_str(&fs::read_to_string(&backups[0]).unwrap()).unwrap();
+        assert_eq!(first, json!({ "save": 4 }));
+        assert!(backups[1].exists());
     }
-    let content = serde_json::to_string_pretty(monitors).unwrap();
-    fs::write(config_path, content).ok();
 }
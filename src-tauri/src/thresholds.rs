@@ -0,0 +1,39 @@
+use crate::types::{AlertLevel, MonitorConfig, ThresholdLevel};
+
+/// The monitor's levels, lowest first. Monitors saved before levels existed
+/// get the warning and critical levels the UI has always coloured by, with
+/// only the critical one notifying as the single threshold used to.
+pub fn levels(monitor: &MonitorConfig) -> Vec<ThresholdLevel> {
+    let mut levels = if monitor.levels.is_empty() {
+        vec![
+            ThresholdLevel {
+                level: AlertLevel::Warning,
+                percent: 80.0,
+                notify: false,
+                message: None,
+            },
+            ThresholdLevel {
+                level: AlertLevel::Critical,
+                percent: 100.0,
+                notify: true,
+                message: None,
+            },
+        ]
+    } else {
+        monitor.levels.clone()
+    };
+    levels.sort_by(|a, b| a.percent.total_cmp(&b.percent));
+    levels
+}
+
+/// The highest level `mb` is over, if any.
+pub fn reached(monitor: &MonitorConfig, mb: f64) -> Option<ThresholdLevel> {
+    levels(monitor)
+        .into_iter()
+        .filter(|l| mb > monitor.threshold * l.percent / 100.0)
+        .max_by_key(|l| l.level)
+}
+
+pub fn level_of(monitor: &MonitorConfig, mb: f64) -> AlertLevel {
+    reached(monitor, mb).map_or(AlertLevel::Ok, |l| l.level)
+}
@@ -13,6 +13,167 @@ pub struct MonitorConfig {
     pub max_depth: Option<usize>, // None or 0 = unlimited
     #[serde(default)]
     pub last_scan_at: Option<u64>,
+    #[serde(default)]
+    pub largest_files_limit: Option<usize>, // None or 0 = don't collect
+    #[serde(default)]
+    pub extension_stats: bool,
+    #[serde(default)]
+    pub age_histogram: bool,
+    #[serde(default)]
+    pub stale_after_days: Option<u32>, // None or 0 = don't track
+    #[serde(default)]
+    pub size_mode: SizeMode,
+    #[serde(default)]
+    pub dedupe_hardlinks: bool,
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    #[serde(default)]
+    pub same_filesystem: bool,
+    #[serde(default)]
+    pub exclude: Vec<String>, // Glob patterns, see `filter::PathFilter`
+    #[serde(default)]
+    pub include: Vec<String>, // Empty = every file
+    #[serde(default)]
+    pub incremental: bool, // Background scans only re-list changed directories
+    #[serde(default)]
+    pub watch: bool, // Keep the size current from file system notifications
+    #[serde(default)]
+    pub interval_minutes: Option<u32>, // None or 0 = global check interval
+    #[serde(default)]
+    pub schedule: Option<String>, // Cron expression, overrides the interval
+    #[serde(default)]
+    pub next_scan_at: Option<u64>,
+    #[serde(default)]
+    pub growth_alert: Option<GrowthAlert>,
+    #[serde(default)]
+    pub levels: Vec<ThresholdLevel>, // Empty = warning at 80%, critical at 100%
+    #[serde(default)]
+    pub max_file_count: Option<u64>, // None or 0 = no file count limit
+    #[serde(default)]
+    pub muted_at: Option<u64>, // Last time a toast's mute action turned `notify` off
+}
+
+/// A new monitor as the app adds it: enabled, silent, 1 GB threshold, every
+/// option off. Callers fill in `id`, `name` and `path`.
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            name: String::new(),
+            path: String::new(),
+            threshold: 1024.0,
+            enabled: true,
+            notify: false,
+            max_depth: None,
+            last_scan_at: None,
+            largest_files_limit: None,
+            extension_stats: false,
+            age_histogram: false,
+            stale_after_days: None,
+            size_mode: SizeMode::default(),
+            dedupe_hardlinks: false,
+            follow_symlinks: false,
+            same_filesystem: false,
+            exclude: Vec::new(),
+            include: Vec::new(),
+            incremental: false,
+            watch: false,
+            interval_minutes: None,
+            schedule: None,
+            next_scan_at: None,
+            growth_alert: None,
+            levels: Vec::new(),
+            max_file_count: None,
+            muted_at: None,
+        }
+    }
+}
+
+/// How close a monitor is to its threshold. Ordered, so the worst of several
+/// compares greatest.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertLevel {
+    #[default]
+    Ok,
+    Warning,
+    Critical,
+}
+
+/// A level a monitor enters once its size passes `percent` of its threshold.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ThresholdLevel {
+    pub level: AlertLevel,
+    pub percent: f64,
+    pub notify: bool,
+    #[serde(default)]
+    pub message: Option<String>, // Replaces the default toast text
+}
+
+/// Where a monitor's threshold alert is: never fired, over a level and
+/// notifying, seen by the user, or back under its levels.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertState {
+    #[default]
+    Ok,
+    Firing,
+    Acknowledged,
+    Resolved,
+}
+
+/// Alert state of one monitor, kept across restarts so a folder that stays
+/// over its threshold isn't reported again on every check.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    /// Over one of the threshold levels, `level` being the highest.
+    #[serde(flatten)]
+    pub threshold: AlertCondition,
+    pub level: AlertLevel,
+    #[serde(default)]
+    pub snoozed_until: Option<u64>, // No notifications for this monitor until then
+    #[serde(default)]
+    pub growth: AlertCondition, // Grew faster than its growth alert allows
+    #[serde(default)]
+    pub file_count: AlertCondition, // Holds more than `max_file_count` files
+}
+
+/// One thing a monitor alerts about, and how far along that alert is.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertCondition {
+    pub state: AlertState,
+    pub changed_at: u64,
+    #[serde(default)]
+    pub notified_at: Option<u64>,
+}
+
+impl AlertCondition {
+    /// Firing or acknowledged, as opposed to never fired or resolved.
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, AlertState::Firing | AlertState::Acknowledged)
+    }
+}
+
+/// Alert when a monitor grows by more than `mb` within `hours`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GrowthAlert {
+    pub mb: f64,
+    pub hours: u32,
+}
+
+/// Which size a monitor counts against its threshold.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SizeMode {
+    /// File lengths as reported by the file system.
+    #[default]
+    Logical,
+    /// Space actually allocated on disk, which accounts for sparse and compressed files.
+    Allocated,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -21,21 +182,46 @@ pub struct AppSettings {
     pub minimize_to_tray: bool,
     #[serde(default = "default_check_interval")]
     pub check_interval_minutes: u32,
+    #[serde(default)]
+    pub scan_workers: u32, // 0 = one per CPU core
+    #[serde(default = "default_renotify")]
+    pub renotify_minutes: u32, // 0 = only when an alert starts or gets worse
+    #[serde(default = "default_hysteresis")]
+    pub hysteresis_percent: f64, // How far under a level the size must drop to clear it
 }
 
 fn default_check_interval() -> u32 {
     60
 }
 
+fn default_renotify() -> u32 {
+    24 * 60
+}
+
+fn default_hysteresis() -> f64 {
+    5.0
+}
+
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
             minimize_to_tray: true,
             check_interval_minutes: 60,
+            scan_workers: 0,
+            renotify_minutes: default_renotify(),
+            hysteresis_percent: default_hysteresis(),
         }
     }
 }
 
+/// Whether the background worker is running scheduled scans.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerStatus {
+    pub paused: bool,
+    pub paused_until: Option<u64>, // None while paused = until resumed
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct MonitorStatus {
@@ -49,23 +235,105 @@ pub struct MonitorStatus {
     pub enabled: bool,
     pub notify: bool,
     pub last_scan_at: Option<u64>,
+    pub stale_bytes: Option<u64>,
+    /// Some entries couldn't be read, so the size is a lower bound.
+    pub partial: bool,
+    pub level: AlertLevel,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CheckResult {
     pub size_bytes: u64,
     pub file_count: u64,
     pub error: Option<String>,
+    pub extensions: Option<Vec<ExtensionStat>>,
+    pub logical_bytes: u64,
+    pub allocated_bytes: u64,
+    pub skipped_count: u64,
+    pub skipped_samples: Vec<SkippedEntry>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ScanProgress {
     pub monitor_id: String,
     pub size_bytes: u64,
     pub file_count: u64,
     pub done: bool,
+    pub cancelled: bool,
     pub error: Option<String>,
     pub last_scan_at: Option<u64>,
+    pub largest_files: Option<Vec<LargeFile>>,
+    pub extensions: Option<Vec<ExtensionStat>>,
+    pub age_buckets: Option<Vec<AgeBucket>>,
+    pub stale_bytes: Option<u64>,
+    pub logical_bytes: Option<u64>,
+    pub allocated_bytes: Option<u64>,
+    pub hardlink_bytes_saved: Option<u64>,
+    pub partial: bool,
+    pub skipped_count: Option<u64>,
+    pub skipped_samples: Option<Vec<SkippedEntry>>,
+    pub reused_dirs: Option<u64>,
+    pub growth_bytes_per_day: Option<f64>,
+    /// When the size is predicted to pass the threshold at the current growth rate.
+    pub threshold_eta: Option<u64>,
+    pub level: Option<AlertLevel>,
+    /// An update from watch mode rather than from a scan.
+    pub live: bool,
+}
+
+/// One directory in a size breakdown, with its children sorted largest first.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeNode {
+    pub name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub file_count: u64,
+    pub children: Vec<SizeNode>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LargeFile {
+    pub path: String,
+    pub size_bytes: u64,
+    pub modified_at: Option<u64>,
+}
+
+/// Bytes and file count for one file extension.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionStat {
+    pub extension: String,
+    pub size_bytes: u64,
+    pub file_count: u64,
+}
+
+/// Bytes and file count for files last modified within one age range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgeBucket {
+    pub label: String,
+    pub max_age_days: Option<u64>, // None = open-ended
+    pub size_bytes: u64,
+    pub file_count: u64,
+}
+
+/// Why a monitor's watch stopped; its size only updates on scans until its
+/// options change or the app restarts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchError {
+    pub monitor_id: String,
+    pub error: String,
+}
+
+/// A file or directory a scan couldn't read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedEntry {
+    pub path: String,
+    pub error: String,
 }
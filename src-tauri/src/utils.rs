@@ -1,4 +1,13 @@
 use std::env;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current Unix timestamp in seconds.
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
 
 pub fn expand_env_vars(path: &str) -> String {
     let mut expanded = path.to_string();
@@ -0,0 +1,619 @@
+use crate::filter::PathFilter;
+use crate::scan_registry::CancelToken;
+use crate::types::SkippedEntry;
+use crossbeam_deque::{Injector, Stealer, Worker};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How many unreadable entries are kept with their error; the rest are only counted.
+const ERROR_SAMPLES: usize = 20;
+
+/// A directory waiting to be read, with its depth below the scan root.
+struct Job {
+    path: PathBuf,
+    depth: usize,
+}
+
+/// What to do with a directory about to be read.
+pub enum DirAction {
+    /// List it as usual.
+    Read,
+    /// Don't list it; its files are already accounted for. Walk these
+    /// subdirectories instead of the ones a listing would find.
+    Reuse(Vec<PathBuf>),
+}
+
+/// State shared by every worker for the duration of one walk.
+struct WalkState {
+    /// Jobs queued or in progress; the walk is finished when this reaches zero
+    pending: AtomicUsize,
+    /// (device, file id) of every directory queued so far, when following symlinks.
+    visited: Mutex<HashSet<(u64, u64)>>,
+    /// Device of the root, when staying on one file system.
+    root_device: Option<u64>,
+    skipped: AtomicU64,
+    samples: Mutex<Vec<SkippedEntry>>,
+    incomplete: Mutex<HashSet<PathBuf>>,
+}
+
+impl WalkState {
+    /// Counts `path`, found while listing `dir`, as unreadable.
+    fn skip(&self, dir: &Path, path: &Path, error: &std::io::Error) {
+        self.skipped.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut incomplete) = self.incomplete.lock() {
+            incomplete.insert(dir.to_path_buf());
+        }
+        if let Ok(mut samples) = self.samples.lock() {
+            if samples.len() < ERROR_SAMPLES {
+                samples.push(SkippedEntry {
+                    path: path.to_string_lossy().to_string(),
+                    error: error.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Entries the walk couldn't read. Their sizes are missing from the totals.
+#[derive(Debug, Default)]
+pub struct WalkErrors {
+    pub skipped: u64,
+    /// The first few, with the reason.
+    pub samples: Vec<SkippedEntry>,
+    /// Directories that couldn't be listed or had an unreadable entry.
+    pub incomplete_dirs: HashSet<PathBuf>,
+}
+
+/// Work-stealing directory walker.
+///
+/// Each worker keeps its own LIFO queue of directories and steals from the
+/// others when it runs dry, so one huge subtree is spread across all threads.
+/// The root is depth 0 and entries at `max_depth` are visited but not descended
+/// into. By default symlinks are not followed and mount points are crossed.
+pub struct ParallelWalker {
+    root: PathBuf,
+    max_depth: Option<usize>,
+    workers: usize,
+    follow_symlinks: bool,
+    same_filesystem: bool,
+    filter: PathFilter,
+}
+
+impl ParallelWalker {
+    /// `max_depth` of `None` or `0` means unlimited, `workers` of `0` means one per CPU core.
+    pub fn new(root: impl Into<PathBuf>, max_depth: Option<usize>, workers: usize) -> Self {
+        let workers = match workers {
+            0 => std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            n => n,
+        };
+
+        Self {
+            root: root.into(),
+            max_depth: max_depth.filter(|d| *d > 0),
+            workers,
+            follow_symlinks: false,
+            same_filesystem: false,
+            filter: PathFilter::default(),
+        }
+    }
+
+    /// Follow symlinks (and junctions on Windows) to files and directories.
+    /// Every directory is read at most once, so link loops end the branch.
+    pub fn follow_symlinks(mut self, enabled: bool) -> Self {
+        self.follow_symlinks = enabled;
+        self
+    }
+
+    /// Don't descend into directories on a different device than the root.
+    pub fn same_filesystem(mut self, enabled: bool) -> Self {
+        self.same_filesystem = enabled;
+        self
+    }
+
+    /// Skip files and prune directories that the filter rejects.
+    pub fn filter(mut self, filter: PathFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Calls `enter` for every directory before it is read and `visit` for every
+    /// regular file, with the worker's own accumulator. Returns one accumulator per
+    /// worker, plus whatever couldn't be read. `metadata` is `None` when the file's
+    /// metadata could not be read.
+    pub fn walk<A, I, E, F>(
+        &self,
+        cancel: &CancelToken,
+        init: I,
+        enter: E,
+        visit: F,
+    ) -> (Vec<A>, WalkErrors)
+    where
+        A: Send,
+        I: Fn() -> A + Sync,
+        E: Fn(&mut A, &Path) -> DirAction + Sync,
+        F: Fn(&mut A, &Path, Option<&fs::Metadata>) + Sync,
+    {
+        let root_meta = match fs::metadata(&self.root) {
+            Ok(meta) => meta,
+            Err(e) => {
+                let errors = WalkErrors {
+                    skipped: 1,
+                    samples: vec![SkippedEntry {
+                        path: self.root.to_string_lossy().to_string(),
+                        error: e.to_string(),
+                    }],
+                    incomplete_dirs: HashSet::new(),
+                };
+                return (Vec::new(), errors);
+            }
+        };
+
+        if !root_meta.is_dir() {
+            let mut acc = init();
+            if root_meta.is_file() {
+                visit(&mut acc, &self.root, Some(&root_meta));
+            }
+            return (vec![acc], WalkErrors::default());
+        }
+
+        let root_id = if self.follow_symlinks || self.same_filesystem {
+            dir_id(&self.root, &root_meta)
+        } else {
+            None
+        };
+        let state = WalkState {
+            pending: AtomicUsize::new(1),
+            visited: Mutex::new(root_id.into_iter().collect()),
+            root_device: root_id.filter(|_| self.same_filesystem).map(|(dev, _)| dev),
+            skipped: AtomicU64::new(0),
+            samples: Mutex::new(Vec::new()),
+            incomplete: Mutex::new(HashSet::new()),
+        };
+
+        let injector = Injector::new();
+        injector.push(Job {
+            path: self.root.clone(),
+            depth: 0,
+        });
+
+        let locals: Vec<Worker<Job>> = (0..self.workers).map(|_| Worker::new_lifo()).collect();
+        let stealers: Vec<Stealer<Job>> = locals.iter().map(|w| w.stealer()).collect();
+
+        let accs = std::thread::scope(|scope| {
+            let handles: Vec<_> = locals
+                .into_iter()
+                .map(|local| {
+                    let injector = &injector;
+                    let stealers = &stealers;
+                    let state = &state;
+                    let init = &init;
+                    let enter = &enter;
+                    let visit = &visit;
+
+                    scope.spawn(move || {
+                        let mut acc = init();
+                        while !cancel.is_cancelled() {
+                            match find_job(&local, injector, stealers) {
+                                Some(job) => {
+                                    match enter(&mut acc, &job.path) {
+                                        DirAction::Read => {
+                                            self.read_dir(job, &local, state, &mut acc, visit)
+                                        }
+                                        DirAction::Reuse(children) => self.push_children(
+                                            job.depth + 1,
+                                            children,
+                                            &local,
+                                            state,
+                                        ),
+                                    }
+                                    state.pending.fetch_sub(1, Ordering::AcqRel);
+                                }
+                                None if state.pending.load(Ordering::Acquire) == 0 => break,
+                                None => std::thread::sleep(Duration::from_micros(100)),
+                            }
+                        }
+                        acc
+                    })
+                })
+                .collect();
+
+            handles.into_iter().filter_map(|h| h.join().ok()).collect()
+        });
+
+        let errors = WalkErrors {
+            skipped: state.skipped.into_inner(),
+            samples: state.samples.into_inner().unwrap_or_default(),
+            incomplete_dirs: state.incomplete.into_inner().unwrap_or_default(),
+        };
+        (accs, errors)
+    }
+
+    fn read_dir<A, F>(
+        &self,
+        job: Job,
+        local: &Worker<Job>,
+        state: &WalkState,
+        acc: &mut A,
+        visit: &F,
+    ) where
+        F: Fn(&mut A, &Path, Option<&fs::Metadata>),
+    {
+        let entries = match fs::read_dir(&job.path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                state.skip(&job.path, &job.path, &e);
+                return;
+            }
+        };
+
+        let depth = job.depth + 1;
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                // The listing itself failed part way, so there's no entry path to report
+                Err(e) => {
+                    state.skip(&job.path, &job.path, &e);
+                    continue;
+                }
+            };
+            let file_type = match entry.file_type() {
+                Ok(t) => t,
+                Err(e) => {
+                    state.skip(&job.path, &entry.path(), &e);
+                    continue;
+                }
+            };
+
+            if file_type.is_file() {
+                let path = entry.path();
+                if !self.skips_file(&path) {
+                    let metadata = entry.metadata();
+                    if let Err(e) = &metadata {
+                        state.skip(&job.path, &path, e);
+                    }
+                    visit(acc, &path, metadata.ok().as_ref());
+                }
+            } else if file_type.is_symlink() && self.follow_symlinks {
+                let path = entry.path();
+                // Broken links are skipped
+                let Ok(target) = fs::metadata(&path) else {
+                    continue;
+                };
+                if target.is_file() {
+                    if !self.skips_file(&path) {
+                        visit(acc, &path, Some(&target));
+                    }
+                } else if target.is_dir()
+                    && self.descends_into(depth)
+                    && !self.skips_dir(&path)
+                    && self.admits(&path, &target, state)
+                {
+                    state.pending.fetch_add(1, Ordering::AcqRel);
+                    local.push(Job { path, depth });
+                }
+            } else if file_type.is_dir() && self.descends_into(depth) {
+                let path = entry.path();
+                if self.skips_dir(&path) {
+                    continue;
+                }
+                let admitted = !(self.follow_symlinks || self.same_filesystem)
+                    || entry
+                        .metadata()
+                        .is_ok_and(|meta| self.admits(&path, &meta, state));
+                if admitted {
+                    state.pending.fetch_add(1, Ordering::AcqRel);
+                    local.push(Job { path, depth });
+                }
+            }
+        }
+    }
+
+    fn push_children(
+        &self,
+        depth: usize,
+        children: Vec<PathBuf>,
+        local: &Worker<Job>,
+        state: &WalkState,
+    ) {
+        if !self.descends_into(depth) {
+            return;
+        }
+        for path in children {
+            state.pending.fetch_add(1, Ordering::AcqRel);
+            local.push(Job { path, depth });
+        }
+    }
+
+    fn skips_file(&self, path: &Path) -> bool {
+        !self.filter.is_empty() && self.filter.skips_file(self.relative(path))
+    }
+
+    fn skips_dir(&self, path: &Path) -> bool {
+        !self.filter.is_empty() && self.filter.skips_dir(self.relative(path))
+    }
+
+    fn relative<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.root).unwrap_or(path)
+    }
+
+    fn admits(&self, path: &Path, metadata: &fs::Metadata, state: &WalkState) -> bool {
+        admits(
+            path,
+            metadata,
+            self.follow_symlinks,
+            state.root_device,
+            |id| {
+                state
+                    .visited
+                    .lock()
+                    .map(|mut visited| visited.insert(id))
+                    .unwrap_or(false)
+            },
+        )
+    }
+
+    fn descends_into(&self, depth: usize) -> bool {
+        match self.max_depth {
+            Some(max) => depth < max,
+            None => true,
+        }
+    }
+}
+
+/// Whether a directory passes the file system and loop checks: it must be on
+/// `root_device` when that is set, and when following symlinks `first_visit`
+/// must accept its id, recording it so a link back to it ends the branch.
+pub(crate) fn admits(
+    path: &Path,
+    metadata: &fs::Metadata,
+    follow_symlinks: bool,
+    root_device: Option<u64>,
+    first_visit: impl FnOnce((u64, u64)) -> bool,
+) -> bool {
+    let Some(id) = dir_id(path, metadata) else {
+        // Can't identify it, so can't tell whether it loops back
+        return !follow_symlinks && root_device.is_none();
+    };
+
+    if root_device.is_some_and(|dev| dev != id.0) {
+        return false;
+    }
+    if follow_symlinks {
+        return first_visit(id);
+    }
+    true
+}
+
+/// (device, file id) identifying a directory.
+#[cfg(unix)]
+pub(crate) fn dir_id(_path: &Path, metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// (volume serial, file index) identifying a directory.
+/// Directory listings don't carry these on Windows, so this opens the directory.
+#[cfg(windows)]
+pub(crate) fn dir_id(path: &Path, _metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_FLAG_BACKUP_SEMANTICS,
+    };
+
+    // Backup semantics is required to open a directory handle
+    let dir = fs::OpenOptions::new()
+        .access_mode(0)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)
+        .ok()?;
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    // SAFETY: the handle stays open for the duration of the call
+    if unsafe { GetFileInformationByHandle(dir.as_raw_handle(), &mut info) } == 0 {
+        return None;
+    }
+    Some((
+        info.dwVolumeSerialNumber as u64,
+        ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64,
+    ))
+}
+
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn dir_id(_path: &Path, _metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+fn find_job(
+    local: &Worker<Job>,
+    injector: &Injector<Job>,
+    stealers: &[Stealer<Job>],
+) -> Option<Job> {
+    local.pop().or_else(|| {
+        std::iter::repeat_with(|| {
+            injector
+                .steal_batch_and_pop(local)
+                .or_else(|| stealers.iter().map(|s| s.steal()).collect())
+        })
+        .find(|s| !s.is_retry())
+        .and_then(|s| s.success())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Nested directories with files of assorted sizes, an empty directory and
+    /// an empty file.
+    fn build_tree(root: &Path) {
+        for (i, dir) in ["", "a", "a/b", "a/b/c", "a/b/c/d", "e", "e/f", "empty"]
+            .iter()
+            .enumerate()
+        {
+            let dir = root.join(dir);
+            fs::create_dir_all(&dir).unwrap();
+            if dir.ends_with("empty") {
+                continue;
+            }
+            for j in 0..5 {
+                fs::write(dir.join(format!("{j}.txt")), vec![b'x'; i * 100 + j * 7]).unwrap();
+            }
+        }
+    }
+
+    /// Size and file count the way scans counted them with `walkdir`.
+    fn walkdir_totals(root: &Path, max_depth: Option<usize>) -> (u64, u64) {
+        let mut walk = walkdir::WalkDir::new(root);
+        if let Some(depth) = max_depth {
+            walk = walk.max_depth(depth);
+        }
+        walk.into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| e.metadata().ok())
+            .fold((0, 0), |(size, count), m| (size + m.len(), count + 1))
+    }
+
+    fn walker_totals(walker: &ParallelWalker) -> (u64, u64) {
+        let (accs, errors) = walker.walk(
+            &CancelToken::default(),
+            || (0, 0),
+            |_, _| DirAction::Read,
+            |acc: &mut (u64, u64), _, metadata| {
+                acc.0 += metadata.map_or(0, |m| m.len());
+                acc.1 += 1;
+            },
+        );
+        assert_eq!(errors.skipped, 0);
+        accs.iter().fold((0, 0), |t, a| (t.0 + a.0, t.1 + a.1))
+    }
+
+    /// Paths of the directories `walker` enters and the files it visits,
+    /// relative to `root`.
+    fn walk_paths(walker: &ParallelWalker, root: &Path) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let relative = |path: &Path| path.strip_prefix(root).unwrap().to_path_buf();
+        let (accs, _) = walker.walk(
+            &CancelToken::default(),
+            || (Vec::new(), Vec::new()),
+            |acc: &mut (Vec<PathBuf>, Vec<PathBuf>), path| {
+                acc.0.push(relative(path));
+                DirAction::Read
+            },
+            |acc, path, _| acc.1.push(relative(path)),
+        );
+        let (mut dirs, mut files): (Vec<_>, Vec<_>) = (Vec::new(), Vec::new());
+        for (d, f) in accs {
+            dirs.extend(d);
+            files.extend(f);
+        }
+        dirs.sort();
+        files.sort();
+        (dirs, files)
+    }
+
+    #[test]
+    fn matches_walkdir() {
+        let dir = tempfile::tempdir().unwrap();
+        build_tree(dir.path());
+
+        for workers in [1, 4] {
+            for depth in [None, Some(1), Some(2), Some(4)] {
+                let walker = ParallelWalker::new(dir.path(), depth, workers);
+                assert_eq!(
+                    walker_totals(&walker),
+                    walkdir_totals(dir.path(), depth),
+                    "{workers} workers, max depth {depth:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn max_depth_visits_entries_at_the_limit_without_descending() {
+        let dir = tempfile::tempdir().unwrap();
+        build_tree(dir.path());
+
+        let files_at = |depth| {
+            let walker = ParallelWalker::new(dir.path(), depth, 2);
+            walk_paths(&walker, dir.path()).1.len()
+        };
+        // Depth 1 is the root's own files, depth 2 adds those in a/, e/ and empty/
+        assert_eq!(files_at(Some(1)), 5);
+        assert_eq!(files_at(Some(2)), 15);
+        assert_eq!(files_at(Some(3)), 25);
+        assert_eq!(files_at(None), 35);
+        assert_eq!(files_at(Some(0)), 35, "0 means unlimited");
+    }
+
+    #[test]
+    fn cancelling_stops_the_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..50 {
+            let sub = dir.path().join(format!("dir{i}"));
+            fs::create_dir(&sub).unwrap();
+            fs::write(sub.join("file"), b"x").unwrap();
+        }
+
+        let cancel = CancelToken::default();
+        let (accs, _) = ParallelWalker::new(dir.path(), None, 1).walk(
+            &cancel,
+            || 0,
+            |_, _| DirAction::Read,
+            |count: &mut u64, _, _| {
+                *count += 1;
+                cancel.cancel();
+            },
+        );
+        let visited: u64 = accs.iter().sum();
+        assert_eq!(visited, 1);
+
+        let (accs, _) = ParallelWalker::new(dir.path(), None, 4).walk(
+            &cancel,
+            || 0,
+            |_, _| DirAction::Read,
+            |count: &mut u64, _, _| *count += 1,
+        );
+        assert_eq!(accs.iter().sum::<u64>(), 0, "already cancelled");
+    }
+
+    #[test]
+    fn filter_prunes_excluded_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        build_tree(dir.path());
+
+        let filter = PathFilter::new(&["b".to_string()], &[]).unwrap();
+        let walker = ParallelWalker::new(dir.path(), None, 2).filter(filter);
+        let (dirs, files) = walk_paths(&walker, dir.path());
+        assert!(
+            dirs.iter().all(|d| !d.starts_with("a/b")),
+            "entered {dirs:?}"
+        );
+        assert_eq!(files.len(), 20);
+
+        let filter = PathFilter::new(&[], &["e/**".to_string()]).unwrap();
+        let walker = ParallelWalker::new(dir.path(), None, 2).filter(filter);
+        let (_, files) = walk_paths(&walker, dir.path());
+        assert_eq!(files.len(), 10);
+        assert!(files.iter().all(|f| f.starts_with("e")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_loops_end_the_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        build_tree(dir.path());
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("a/b/to_root")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("e"), dir.path().join("to_e")).unwrap();
+
+        let plain = ParallelWalker::new(dir.path(), None, 4);
+        let followed = ParallelWalker::new(dir.path(), None, 4).follow_symlinks(true);
+        // Both links lead to directories already walked, so nothing is counted twice
+        assert_eq!(walker_totals(&followed), walker_totals(&plain));
+    }
+}
@@ -0,0 +1,557 @@
+use crate::filter::PathFilter;
+use crate::scan_registry::CancelToken;
+use crate::scanner::{self, Scanner};
+use crate::snapshot::DirRecord;
+use crate::state::AppState;
+use crate::types::{MonitorConfig, ScanProgress, SizeMode, WatchError};
+use crate::walker;
+use crate::{show_threshold_toast, utils};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+/// How long the tree has to be quiet before changed directories are re-read.
+const SETTLE: Duration = Duration::from_millis(750);
+
+/// Changed directories are re-read after this long even if events keep coming.
+const MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Watched monitors are fully rescanned this often, in case events were missed.
+const FULL_RESCAN_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Monitors in watch mode, each with a thread that keeps its size current
+/// from file system notifications.
+#[derive(Clone, Default)]
+pub struct WatchRegistry {
+    watches: Arc<Mutex<HashMap<String, WatchHandle>>>,
+    /// Watch keys of monitors whose watch couldn't start, so `sync` doesn't
+    /// retry them until their options change.
+    failed: Arc<Mutex<HashMap<String, String>>>,
+    /// Set while monitoring is paused, so every watch is stopped.
+    paused: Arc<AtomicBool>,
+}
+
+struct WatchHandle {
+    /// Options that decide what is counted; the watch restarts when they change.
+    key: String,
+    /// Latest saved config, for the name, threshold and notify flag.
+    monitor: Arc<Mutex<MonitorConfig>>,
+    stop: CancelToken,
+}
+
+impl WatchRegistry {
+    /// Takes effect on the next `sync`.
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// Starts watching enabled monitors in watch mode, stops watching the rest,
+    /// and restarts watches whose scan options changed.
+    pub fn sync(&self, app_handle: &tauri::AppHandle, monitors: &[MonitorConfig]) {
+        let (Ok(mut watches), Ok(mut failed)) = (self.watches.lock(), self.failed.lock()) else {
+            return;
+        };
+        let paused = self.paused.load(Ordering::Relaxed);
+        let wanted: HashMap<&str, &MonitorConfig> = monitors
+            .iter()
+            .filter(|m| !paused && m.enabled && watches_live(m))
+            .map(|m| (m.id.as_str(), m))
+            .collect();
+
+        failed.retain(|id, key| {
+            wanted
+                .get(id.as_str())
+                .is_some_and(|monitor| watch_key(monitor) == *key)
+        });
+
+        watches.retain(|id, handle| match wanted.get(id.as_str()) {
+            Some(monitor) if watch_key(monitor) == handle.key => {
+                if let Ok(mut current) = handle.monitor.lock() {
+                    *current = (*monitor).clone();
+                }
+                true
+            }
+            _ => {
+                handle.stop.cancel();
+                false
+            }
+        });
+
+        for (id, monitor) in wanted {
+            if watches.contains_key(id) || failed.contains_key(id) {
+                continue;
+            }
+
+            let handle = WatchHandle {
+                key: watch_key(monitor),
+                monitor: Arc::new(Mutex::new(monitor.clone())),
+                stop: CancelToken::default(),
+            };
+            let app_handle = app_handle.clone();
+            let registry = self.clone();
+            let shared = handle.monitor.clone();
+            let stop = handle.stop.clone();
+            std::thread::spawn(move || run_watch(app_handle, registry, shared, stop));
+            watches.insert(id.to_string(), handle);
+        }
+    }
+
+    /// Drops a watch that couldn't start or keep going and tells the UI why.
+    fn fail(
+        &self,
+        app_handle: &tauri::AppHandle,
+        monitor: &MonitorConfig,
+        stop: &CancelToken,
+        error: String,
+    ) {
+        // Stopped on purpose, and possibly already replaced by a new watch
+        if stop.is_cancelled() {
+            return;
+        }
+        let (Ok(mut watches), Ok(mut failed)) = (self.watches.lock(), self.failed.lock()) else {
+            return;
+        };
+        let key = watch_key(monitor);
+        if watches.get(&monitor.id).is_some_and(|h| h.key == key) {
+            watches.remove(&monitor.id);
+        }
+        failed.insert(monitor.id.clone(), key);
+        drop((watches, failed));
+
+        app_handle
+            .emit(
+                "watch-error",
+                WatchError {
+                    monitor_id: monitor.id.clone(),
+                    error,
+                },
+            )
+            .ok();
+    }
+}
+
+/// Whether a monitor's size is kept current from notifications. Hard-link
+/// deduplication needs every file in the tree compared, which the per-directory
+/// live totals can't do, so those monitors are only updated by scans.
+fn watches_live(monitor: &MonitorConfig) -> bool {
+    monitor.watch && !monitor.dedupe_hardlinks
+}
+
+fn watch_key(monitor: &MonitorConfig) -> String {
+    format!(
+        "{}|{:?}|{:?}|{}|{}|{:?}|{:?}",
+        monitor.path,
+        monitor.max_depth,
+        monitor.size_mode,
+        monitor.follow_symlinks,
+        monitor.same_filesystem,
+        monitor.exclude,
+        monitor.include
+    )
+}
+
+fn run_watch(
+    app_handle: tauri::AppHandle,
+    registry: WatchRegistry,
+    monitor: Arc<Mutex<MonitorConfig>>,
+    stop: CancelToken,
+) {
+    let Ok(config) = monitor.lock().map(|m| m.clone()) else {
+        return;
+    };
+    let workers = app_handle.state::<AppState>().settings().scan_workers as usize;
+
+    let (tx, rx) = mpsc::channel();
+    let root = PathBuf::from(utils::expand_env_vars(&config.path));
+    let watcher = notify::recommended_watcher(tx).and_then(|mut watcher| {
+        watcher.watch(&root, RecursiveMode::Recursive)?;
+        Ok(watcher)
+    });
+    // Held until the loop ends; dropping it stops the events
+    let _watcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            registry.fail(&app_handle, &config, &stop, e.to_string());
+            return;
+        }
+    };
+
+    let mut tree = match LiveTree::scan(&config, workers, &stop) {
+        Ok(tree) => tree,
+        Err(e) => {
+            registry.fail(&app_handle, &config, &stop, e);
+            return;
+        }
+    };
+    let mut last_full = Instant::now();
+    tree.report(&app_handle, &config);
+
+    let mut dirty: HashSet<PathBuf> = HashSet::new();
+    let mut dirty_since: Option<Instant> = None;
+    let mut last_event = Instant::now();
+    let mut rescan = false;
+
+    while !stop.is_cancelled() {
+        match rx.recv_timeout(Duration::from_millis(250)) {
+            Ok(Ok(event)) => {
+                if event.need_rescan() {
+                    rescan = true;
+                } else if !matches!(event.kind, EventKind::Access(_)) {
+                    for path in &event.paths {
+                        tree.mark_dirty(path, &mut dirty);
+                    }
+                    dirty_since.get_or_insert_with(Instant::now);
+                }
+                last_event = Instant::now();
+            }
+            // Usually a queue overflow, so some events are lost
+            Ok(Err(_)) => rescan = true,
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        let changed = if rescan || last_full.elapsed() >= FULL_RESCAN_INTERVAL {
+            match LiveTree::scan(&config, workers, &stop) {
+                Ok(fresh) => tree = fresh,
+                Err(_) if stop.is_cancelled() => break,
+                Err(_) => {}
+            }
+            rescan = false;
+            last_full = Instant::now();
+            dirty.clear();
+            dirty_since = None;
+            true
+        } else if dirty_since
+            .is_some_and(|since| last_event.elapsed() >= SETTLE || since.elapsed() >= MAX_DELAY)
+        {
+            // Parents first, so a new directory is only read once
+            let mut dirs: Vec<PathBuf> = dirty.drain().collect();
+            dirs.sort_by_key(|d| d.components().count());
+            for dir in dirs {
+                tree.refresh(dir);
+            }
+            dirty_since = None;
+            true
+        } else {
+            false
+        };
+
+        if changed {
+            let Ok(current) = monitor.lock().map(|m| m.clone()) else {
+                break;
+            };
+            tree.report(&app_handle, &current);
+        }
+    }
+}
+
+/// Per-directory totals for a watched monitor, kept current from change events.
+struct LiveTree {
+    root: PathBuf,
+    max_depth: Option<usize>,
+    size_mode: SizeMode,
+    follow_symlinks: bool,
+    /// Device of the root, when staying on one file system.
+    root_device: Option<u64>,
+    filter: PathFilter,
+    /// Allocation unit of the root's volume.
+    cluster: u64,
+    /// Files directly inside each directory, keyed relative to the root.
+    dirs: HashMap<PathBuf, DirRecord>,
+    /// The directory in `dirs` each (device, file id) belongs to, when
+    /// following symlinks, so a link back to one isn't walked again.
+    ids: HashMap<(u64, u64), PathBuf>,
+}
+
+impl LiveTree {
+    /// Walks the whole tree to get a fresh baseline.
+    fn scan(config: &MonitorConfig, workers: usize, stop: &CancelToken) -> Result<Self, String> {
+        let summary = Scanner::new(&config.path)
+            .max_depth(config.max_depth)
+            .walk_options(config)
+            .workers(workers)
+            .cancel_token(stop.clone())
+            .record_snapshot(true)
+            .run(&|_, _| {})?;
+
+        let root = PathBuf::from(utils::expand_env_vars(&config.path));
+        let dirs = summary.snapshot.map(|s| s.dirs).unwrap_or_default();
+        let id_of = |relative: &Path| {
+            let path = root.join(relative);
+            fs::metadata(&path)
+                .ok()
+                .and_then(|meta| walker::dir_id(&path, &meta))
+        };
+        let root_device = match config.same_filesystem {
+            true => id_of(Path::new("")).map(|(dev, _)| dev),
+            false => None,
+        };
+        let ids = match config.follow_symlinks {
+            true => dirs
+                .keys()
+                .filter_map(|relative| Some((id_of(relative)?, relative.clone())))
+                .collect(),
+            false => HashMap::new(),
+        };
+
+        Ok(Self {
+            max_depth: config.max_depth.filter(|d| *d > 0),
+            size_mode: config.size_mode,
+            follow_symlinks: config.follow_symlinks,
+            root_device,
+            filter: PathFilter::new(&config.exclude, &config.include)?,
+            cluster: scanner::cluster_size(&root),
+            dirs,
+            ids,
+            root,
+        })
+    }
+
+    fn size_bytes(&self) -> u64 {
+        self.dirs
+            .values()
+            .map(|d| match self.size_mode {
+                SizeMode::Logical => d.logical_bytes,
+                SizeMode::Allocated => d.allocated_bytes,
+            })
+            .sum()
+    }
+
+    fn size_mb(&self) -> f64 {
+        self.size_bytes() as f64 / (1024.0 * 1024.0)
+    }
+
+    /// Sends the current size to the UI and through the monitor's alert, which
+    /// decides whether the change is worth a toast.
+    fn report(&self, app_handle: &tauri::AppHandle, monitor: &MonitorConfig) {
+        let mb = self.size_mb();
+        let (level, notify) = app_handle
+            .state::<AppState>()
+            .update_alert(app_handle, monitor, mb);
+        if let Some(reached) = notify {
+            show_threshold_toast(app_handle, monitor, &reached, mb);
+        }
+        app_handle
+            .emit(
+                "scan-progress",
+                ScanProgress {
+                    monitor_id: monitor.id.clone(),
+                    size_bytes: self.size_bytes(),
+                    file_count: self.dirs.values().map(|d| d.file_count).sum(),
+                    level: Some(level),
+                    live: true,
+                    ..Default::default()
+                },
+            )
+            .ok();
+    }
+
+    /// Queues the directories an event for `path` may have changed: the one
+    /// containing it, and the path itself if it is a directory.
+    ///
+    /// Only directories the tree already holds are queued. New ones are picked
+    /// up by listing their parent, which applies the same rules as the scan.
+    fn mark_dirty(&self, path: &Path, dirty: &mut HashSet<PathBuf>) {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return;
+        };
+        let parent = relative.parent().into_iter();
+        for dir in parent.chain(Some(relative)) {
+            if self.holds(dir) {
+                dirty.insert(dir.to_path_buf());
+            }
+        }
+    }
+
+    /// Whether `relative` is the root or a directory the walk took in.
+    fn holds(&self, relative: &Path) -> bool {
+        relative.as_os_str().is_empty() || self.dirs.contains_key(relative)
+    }
+
+    /// Whether the walk would list a directory at this depth below the root.
+    fn reads(&self, depth: usize) -> bool {
+        match self.max_depth {
+            Some(max) => depth < max,
+            None => true,
+        }
+    }
+
+    /// Re-reads one directory's files, picking up new subdirectories and
+    /// dropping ones that are gone.
+    fn refresh(&mut self, relative: PathBuf) {
+        if !self.holds(&relative) {
+            return;
+        }
+        let mut queue = vec![relative];
+        while let Some(relative) = queue.pop() {
+            let depth = relative.components().count();
+            let excluded = depth > 0 && self.filter.skips_dir(&relative);
+            let entries = match fs::read_dir(self.root.join(&relative)) {
+                Ok(entries) if self.reads(depth) && !excluded => entries,
+                _ => {
+                    self.remove_subtree(&relative);
+                    continue;
+                }
+            };
+
+            let mut record = DirRecord::default();
+            for entry in entries.flatten() {
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                let path = entry.path();
+                let metadata = if file_type.is_symlink() {
+                    if !self.follow_symlinks {
+                        continue;
+                    }
+                    fs::metadata(&path)
+                } else {
+                    entry.metadata()
+                };
+                let Ok(metadata) = metadata else {
+                    continue;
+                };
+
+                let child = relative.join(entry.file_name());
+                if metadata.is_file() {
+                    if !self.filter.skips_file(&child) {
+                        record.logical_bytes += metadata.len();
+                        record.allocated_bytes +=
+                            scanner::allocated_size(&path, &metadata, self.cluster);
+                        record.file_count += 1;
+                    }
+                } else if metadata.is_dir()
+                    && self.reads(depth + 1)
+                    && !self.filter.skips_dir(&child)
+                    && (self.dirs.contains_key(&child) || self.admits(&child, &path, &metadata))
+                {
+                    record
+                        .subdirs
+                        .push(entry.file_name().to_string_lossy().to_string());
+                    if !self.dirs.contains_key(&child) {
+                        queue.push(child);
+                    }
+                }
+            }
+
+            let gone: Vec<PathBuf> = self
+                .dirs
+                .get(&relative)
+                .map(|old| {
+                    old.subdirs
+                        .iter()
+                        .filter(|name| !record.subdirs.contains(name))
+                        .map(|name| relative.join(name))
+                        .collect()
+                })
+                .unwrap_or_default();
+            for dir in gone {
+                self.remove_subtree(&dir);
+            }
+            self.dirs.insert(relative, record);
+        }
+    }
+
+    /// Whether a directory new to the tree passes the same file system and
+    /// loop checks as the walk that built it.
+    fn admits(&mut self, relative: &Path, path: &Path, metadata: &fs::Metadata) -> bool {
+        if !self.follow_symlinks && self.root_device.is_none() {
+            return true;
+        }
+        let ids = &mut self.ids;
+        walker::admits(
+            path,
+            metadata,
+            self.follow_symlinks,
+            self.root_device,
+            |id| match ids.get(&id) {
+                Some(owner) => owner == relative,
+                None => {
+                    ids.insert(id, relative.to_path_buf());
+                    true
+                }
+            },
+        )
+    }
+
+    fn remove_subtree(&mut self, relative: &Path) {
+        self.dirs.retain(|dir, _| !dir.starts_with(relative));
+        self.ids.retain(|_, dir| !dir.starts_with(relative));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn refresh_stops_at_links_back_up_the_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        fs::write(dir.path().join("a/one"), b"12345").unwrap();
+        fs::write(dir.path().join("a/b/two"), b"12345").unwrap();
+        let config = MonitorConfig {
+            path: dir.path().to_string_lossy().to_string(),
+            follow_symlinks: true,
+            ..Default::default()
+        };
+        let mut tree = LiveTree::scan(&config, 1, &CancelToken::default()).unwrap();
+        assert_eq!(tree.size_bytes(), 10);
+
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("a/b/up")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("a/b"), dir.path().join("b")).unwrap();
+        tree.refresh(PathBuf::from("a/b"));
+        tree.refresh(PathBuf::new());
+        assert_eq!(tree.size_bytes(), 10);
+        assert_eq!(tree.dirs.len(), 3);
+
+        // Once the original goes, the link is the only way to it
+        fs::remove_file(dir.path().join("a/b/up")).unwrap();
+        fs::rename(dir.path().join("a/b"), dir.path().join("c")).unwrap();
+        fs::remove_file(dir.path().join("b")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("c"), dir.path().join("b")).unwrap();
+        tree.refresh(PathBuf::from("a"));
+        tree.refresh(PathBuf::new());
+        assert_eq!(tree.size_bytes(), 10);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn changes_inside_excluded_directories_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+        fs::write(dir.path().join("one"), b"12345").unwrap();
+        let config = MonitorConfig {
+            path: dir.path().to_string_lossy().to_string(),
+            exclude: vec!["node_modules".to_string()],
+            ..Default::default()
+        };
+        let mut tree = LiveTree::scan(&config, 1, &CancelToken::default()).unwrap();
+        assert_eq!(tree.size_bytes(), 5);
+
+        let written = dir.path().join("node_modules/pkg/new");
+        fs::write(&written, [0u8; 2000]).unwrap();
+        fs::create_dir(dir.path().join("node_modules/pkg/sub")).unwrap();
+        let mut dirty = HashSet::new();
+        tree.mark_dirty(&written, &mut dirty);
+        tree.mark_dirty(&dir.path().join("node_modules/pkg/sub"), &mut dirty);
+        assert!(dirty.is_empty());
+        tree.refresh(PathBuf::from("node_modules/pkg"));
+        assert_eq!(tree.size_bytes(), 5);
+
+        // A new directory outside the excluded one comes in through its parent
+        fs::create_dir(dir.path().join("new")).unwrap();
+        fs::write(dir.path().join("new/two"), b"12345").unwrap();
+        tree.mark_dirty(&dir.path().join("new"), &mut dirty);
+        assert_eq!(dirty, HashSet::from([PathBuf::new()]));
+        tree.refresh(PathBuf::new());
+        assert_eq!(tree.size_bytes(), 10);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::types::WorkerStatus;
+use crate::utils;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use tauri::Emitter;
+
+/// Requests sent to the background worker from commands and the tray menu.
+pub enum WorkerCommand {
+    /// Scan every enabled monitor now, whether due or not.
+    CheckNow,
+    /// Stop scheduled scans and watches, until the given time or `Resume`.
+    Pause(Option<u64>),
+    Resume,
+}
+
+/// Sends commands to the background worker and reports whether it is paused.
+#[derive(Clone)]
+pub struct WorkerControl {
+    sender: Sender<WorkerCommand>,
+    status: Arc<Mutex<WorkerStatus>>,
+}
+
+impl WorkerControl {
+    /// The receiver is handed to the worker thread.
+    pub fn new() -> (Self, Receiver<WorkerCommand>) {
+        let (sender, receiver) = mpsc::channel();
+        let control = Self {
+            sender,
+            status: Arc::default(),
+        };
+        (control, receiver)
+    }
+
+    pub fn send(&self, command: WorkerCommand) {
+        self.sender.send(command).ok();
+    }
+
+    pub fn status(&self) -> WorkerStatus {
+        self.status.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Applies a pause or resume on the worker's side and tells the UI.
+    /// Returns whether the command asks for an immediate check.
+    pub fn apply(&self, app_handle: &tauri::AppHandle, command: WorkerCommand) -> bool {
+        let status = match command {
+            WorkerCommand::CheckNow => return true,
+            WorkerCommand::Pause(until) => WorkerStatus {
+                paused: true,
+                paused_until: until,
+            },
+            WorkerCommand::Resume => WorkerStatus::default(),
+        };
+        self.publish(app_handle, status);
+        false
+    }
+
+    /// The current status, ending a timed pause that has run out.
+    pub fn refresh(&self, app_handle: &tauri::AppHandle) -> WorkerStatus {
+        let status = self.status();
+        match status.paused_until {
+            Some(until) if status.paused && until <= utils::now_secs() => {
+                self.publish(app_handle, WorkerStatus::default());
+                WorkerStatus::default()
+            }
+            _ => status,
+        }
+    }
+
+    fn publish(&self, app_handle: &tauri::AppHandle, status: WorkerStatus) {
+        if let Ok(mut current) = self.status.lock() {
+            *current = status.clone();
+        }
+        app_handle.emit("worker-status", status).ok();
+    }
+}
@@ -1,12 +1,14 @@
 import { useState, useMemo, useEffect } from "react";
 import { AppShell, Container, Text, Title, ThemeIcon } from "@mantine/core";
 import { useDisclosure } from '@mantine/hooks';
+import { notifications } from "@mantine/notifications";
 import { listen } from "@tauri-apps/api/event";
 import { IconFolderPlus } from "@tabler/icons-react";
 import "./App.css";
 
 import { MonitorStatus } from "./types";
 import { api } from "./api";
+import { errorMessage } from "./utils";
 import { useMonitors } from "./hooks/useMonitors";
 import { TitleBar } from "./components/TitleBar";
 import { StatsGrid } from "./components/StatsGrid";
@@ -14,6 +16,8 @@ import { MonitorTable } from "./components/MonitorTable";
 import { AddMonitorModal } from "./components/AddMonitorModal";
 import { EditMonitorModal } from "./components/EditMonitorModal";
 import { SettingsModal } from "./components/SettingsModal";
+import { SizeBreakdownModal } from "./components/SizeBreakdownModal";
+import { LargestFilesModal } from "./components/LargestFilesModal";
 import { StatusBar } from "./components/StatusBar";
 
 function App() {
@@ -22,6 +26,8 @@ function App() {
     scanning,
     settings,
     lastAutoCheck,
+    workerStatus,
+    alerts,
     stats,
     actions
   } = useMonitors();
@@ -46,20 +52,37 @@ function App() {
   const [addOpened, { open: openAdd, close: closeAdd }] = useDisclosure(false);
   const [editOpened, { open: openEdit, close: closeEdit }] = useDisclosure(false);
   const [settingsOpened, { open: openSettings, close: closeSettings }] = useDisclosure(false);
+  const [breakdownOpened, { open: openBreakdown, close: closeBreakdown }] = useDisclosure(false);
+  const [largestOpened, { open: openLargest, close: closeLargest }] = useDisclosure(false);
 
   const [editingMonitor, setEditingMonitor] = useState<MonitorStatus | null>(null);
+  const [breakdownMonitor, setBreakdownMonitor] = useState<MonitorStatus | null>(null);
+  const [largestMonitor, setLargestMonitor] = useState<MonitorStatus | null>(null);
 
   const startEdit = (monitor: MonitorStatus) => {
     setEditingMonitor(monitor);
     openEdit();
   };
 
+  const showBreakdown = (monitor: MonitorStatus) => {
+    setBreakdownMonitor(monitor);
+    openBreakdown();
+  };
+
+  const showLargestFiles = (monitor: MonitorStatus) => {
+    setLargestMonitor(monitor);
+    openLargest();
+  };
+
   const handleRestore = async () => {
     await actions.handleRestore();
     closeSettings();
   };
 
-  const openFolder = (path: string) => api.openMonitorPath(path).catch(err => console.error(err));
+  const openFolder = (path: string) => api.openMonitorPath(path).catch(err => {
+    console.error(err);
+    notifications.show({ title: "Couldn't Open Folder", message: errorMessage(err), color: "red" });
+  });
   const handleOpenConfig = () => api.openConfigFolder().catch(console.error);
 
   const setSorting = (field: string) => {
@@ -110,6 +133,18 @@ function App() {
         onSave={actions.handleEditSave}
       />
 
+      <SizeBreakdownModal
+        opened={breakdownOpened}
+        onClose={closeBreakdown}
+        monitor={breakdownMonitor}
+      />
+
+      <LargestFilesModal
+        opened={largestOpened}
+        onClose={closeLargest}
+        monitor={largestMonitor}
+      />
+
       <SettingsModal
         opened={settingsOpened}
         settings={settings}
@@ -132,6 +167,7 @@ function App() {
 
               <MonitorTable
                 data={sortedData}
+                alerts={alerts}
                 sortBy={sortBy}
                 reverseSortDirection={reverseSortDirection}
                 onSort={setSorting}
@@ -141,6 +177,10 @@ function App() {
                 onToggleNotify={actions.handleToggleNotify}
                 onToggleEnabled={actions.handleToggleEnabled}
                 onScanOne={actions.scanOneStreaming}
+                onShowBreakdown={showBreakdown}
+                onShowLargestFiles={showLargestFiles}
+                onSnoozeAlert={actions.snoozeAlert}
+                onAcknowledgeAlert={actions.acknowledgeAlert}
               />
             </div>
           </Container>
@@ -150,6 +190,10 @@ function App() {
             locationCount={monitors.length}
             lastAutoCheck={lastAutoCheck}
             isScanning={scanning}
+            workerStatus={workerStatus}
+            onCheckNow={actions.checkNow}
+            onPause={actions.pauseMonitoring}
+            onResume={actions.resumeMonitoring}
           />
         </AppShell.Footer>
       </AppShell>
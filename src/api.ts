@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import { MonitorConfig, AppSettings } from "./types";
+import { MonitorConfig, AppSettings, SizeNode, LargeFile, WorkerStatus, HistoryPoint, HistoryRange, Alert } from "./types";
 
 /**
  * API module for centralized Tauri commands.
@@ -16,6 +16,33 @@ export const api = {
     saveSettings: (settings: AppSettings): Promise<void> =>
         invoke("save_settings", { settings }),
 
+    /**
+     * Background worker
+     */
+    triggerBackgroundCheck: (): Promise<void> =>
+        invoke("trigger_background_check"),
+
+    pauseMonitoring: (duration?: number): Promise<void> =>
+        invoke("pause_monitoring", { duration: duration ?? null }),
+
+    resumeMonitoring: (): Promise<void> =>
+        invoke("resume_monitoring"),
+
+    getWorkerStatus: (): Promise<WorkerStatus> =>
+        invoke("get_worker_status"),
+
+    /**
+     * Alerts
+     */
+    getAlerts: (): Promise<Record<string, Alert>> =>
+        invoke("get_alerts"),
+
+    snoozeAlert: (monitorId: string, hours: number): Promise<void> =>
+        invoke("snooze_alert", { monitorId, hours }),
+
+    acknowledgeAlert: (monitorId: string): Promise<void> =>
+        invoke("acknowledge_alert", { monitorId }),
+
     /**
      * Monitors
      */
@@ -25,19 +52,37 @@ export const api = {
     saveMonitors: (monitors: MonitorConfig[]): Promise<void> =>
         invoke("save_monitors", { monitors }),
 
+    getStorageWarnings: (): Promise<string[]> =>
+        invoke("get_storage_warnings"),
+
     restoreDefaults: (): Promise<MonitorConfig[]> =>
         invoke("restore_defaults"),
 
     /**
      * Scanning
      */
-    checkMonitorPathStreaming: (monitorId: string, path: string, maxDepth?: number): Promise<void> =>
+    checkMonitorPathStreaming: (monitorId: string, path: string, maxDepth?: number): Promise<boolean> =>
         invoke("check_monitor_path_streaming", {
             monitorId,
             path,
             maxDepth: maxDepth || null
         }),
 
+    cancelScan: (monitorId: string): Promise<boolean> =>
+        invoke("cancel_scan", { monitorId }),
+
+    checkSchedule: (expression: string): Promise<number> =>
+        invoke("check_schedule", { expression }),
+
+    getMonitorHistory: (id: string, range: HistoryRange): Promise<HistoryPoint[]> =>
+        invoke("get_monitor_history", { id, range }),
+
+    getSizeBreakdown: (monitorId: string, depth: number): Promise<SizeNode> =>
+        invoke("get_size_breakdown", { monitorId, depth }),
+
+    getLargestFiles: (monitorId: string, limit: number): Promise<LargeFile[]> =>
+        invoke("get_largest_files", { monitorId, limit }),
+
     /**
      * System/File Operations
      */
@@ -1,12 +1,12 @@
 import { Modal } from "@mantine/core";
 import { MonitorStatus } from "../types";
-import { MonitorForm } from "./MonitorForm";
+import { MonitorForm, MonitorFormValues } from "./MonitorForm";
 
 interface EditMonitorModalProps {
     opened: boolean;
     onClose: () => void;
     monitor: MonitorStatus | null;
-    onSave: (id: string, name: string, path: string, threshold: number, maxDepth: number | undefined, enabled: boolean) => void;
+    onSave: (id: string, values: MonitorFormValues) => void;
 }
 
 export function EditMonitorModal({ opened, onClose, monitor, onSave }: Readonly<EditMonitorModalProps>) {
@@ -18,13 +18,30 @@ export function EditMonitorModal({ opened, onClose, monitor, onSave }: Readonly<
                     path: monitor.path,
                     threshold: monitor.threshold,
                     maxDepth: monitor.maxDepth,
-                    enabled: monitor.enabled
+                    enabled: monitor.enabled,
+                    largestFilesLimit: monitor.largestFilesLimit,
+                    extensionStats: monitor.extensionStats ?? false,
+                    ageHistogram: monitor.ageHistogram ?? false,
+                    staleAfterDays: monitor.staleAfterDays,
+                    sizeMode: monitor.sizeMode ?? "logical",
+                    dedupeHardlinks: monitor.dedupeHardlinks ?? false,
+                    followSymlinks: monitor.followSymlinks ?? false,
+                    sameFilesystem: monitor.sameFilesystem ?? false,
+                    exclude: monitor.exclude ?? [],
+                    include: monitor.include ?? [],
+                    incremental: monitor.incremental ?? false,
+                    watch: monitor.watch ?? false,
+                    intervalMinutes: monitor.intervalMinutes,
+                    schedule: monitor.schedule ?? '',
+                    growthAlert: monitor.growthAlert,
+                    levels: monitor.levels ?? [],
+                    maxFileCount: monitor.maxFileCount
                 } : undefined}
                 submitLabel="Save Changes"
                 showExtendedFields
                 onSubmit={(values) => {
                     if (monitor) {
-                        onSave(monitor.id, values.name, values.path, values.threshold, values.maxDepth, values.enabled);
+                        onSave(monitor.id, values);
                     }
                     onClose();
                 }}
@@ -0,0 +1,104 @@
+import { useEffect, useState } from "react";
+import { Modal, Stack, Group, Text, Loader, Center, ScrollArea, Button, ActionIcon, Tooltip } from "@mantine/core";
+import { IconFolderOpen, IconRefresh } from "@tabler/icons-react";
+import { LargeFile, MonitorStatus } from "../types";
+import { AppColors } from "../theme";
+import { errorMessage, formatBytes, formatRelativeTime } from "../utils";
+import { api } from "../api";
+
+const DEFAULT_LIMIT = 25;
+
+interface LargestFilesModalProps {
+    readonly opened: boolean;
+    readonly onClose: () => void;
+    readonly monitor: MonitorStatus | null;
+}
+
+const splitPath = (path: string): { dir: string; file: string } => {
+    const idx = Math.max(path.lastIndexOf('\\'), path.lastIndexOf('/'));
+    return { dir: path.substring(0, idx), file: path.substring(idx + 1) };
+};
+
+export function LargestFilesModal({ opened, onClose, monitor }: Readonly<LargestFilesModalProps>) {
+    const [files, setFiles] = useState<LargeFile[] | null>(null);
+    const [error, setError] = useState<string | null>(null);
+    const [refreshKey, setRefreshKey] = useState(0);
+
+    useEffect(() => {
+        if (!opened || !monitor) return;
+
+        // Reuse the list from the last scan when this monitor collects one
+        if (refreshKey === 0 && monitor.largestFiles) {
+            setFiles(monitor.largestFiles);
+            setError(null);
+            return;
+        }
+
+        let active = true;
+        let pending = true;
+        setFiles(null);
+        setError(null);
+        api.getLargestFiles(monitor.id, monitor.largestFilesLimit || DEFAULT_LIMIT)
+            .then(result => { if (active) setFiles(result); })
+            .catch(e => { if (active) setError(errorMessage(e)); })
+            .finally(() => { pending = false; });
+        return () => {
+            active = false;
+            // Stop the walk if the modal is closed before it finishes
+            if (pending) api.cancelScan(monitor.id);
+        };
+    }, [opened, monitor, refreshKey]);
+
+    useEffect(() => {
+        if (!opened) setRefreshKey(0);
+    }, [opened]);
+
+    let content = <Center py="xl"><Loader size="sm" /></Center>;
+    if (error) {
+        content = <Text size="sm" c={AppColors.danger}>{error}</Text>;
+    } else if (files) {
+        content = files.length === 0 ? (
+            <Text size="sm" c="dimmed">No files found.</Text>
+        ) : (
+            <Stack gap={6}>
+                {files.map(f => {
+                    const { dir, file } = splitPath(f.path);
+                    return (
+                        <Group key={f.path} gap="xs" wrap="nowrap">
+                            <div style={{ flex: 1, minWidth: 0 }}>
+                                <Text size="sm" truncate="end" title={f.path}>{file}</Text>
+                                <Text size="xs" c="dimmed" truncate="start" style={{ fontFamily: 'monospace' }}>{dir}</Text>
+                            </div>
+                            <Text size="xs" c="dimmed" w={70} ta="right">{formatRelativeTime(f.modifiedAt)}</Text>
+                            <Text size="sm" fw={600} w={80} ta="right" style={{ fontVariantNumeric: 'tabular-nums' }}>{formatBytes(f.sizeBytes)}</Text>
+                            <Tooltip label="Open Folder">
+                                <ActionIcon variant="subtle" color={AppColors.neutral} size="sm" onClick={() => api.openMonitorPath(dir).catch(console.error)}>
+                                    <IconFolderOpen size={14} />
+                                </ActionIcon>
+                            </Tooltip>
+                        </Group>
+                    );
+                })}
+            </Stack>
+        );
+    }
+
+    return (
+        <Modal opened={opened} onClose={onClose} title={`Largest Files${monitor ? ` - ${monitor.name}` : ''}`} size="lg" centered>
+            <Stack>
+                <Group justify="flex-end">
+                    <Button
+                        size="xs"
+                        variant="light"
+                        leftSection={<IconRefresh size={14} />}
+                        loading={!files && !error}
+                        onClick={() => setRefreshKey(k => k + 1)}
+                    >
+                        Rescan
+                    </Button>
+                </Group>
+                <ScrollArea.Autosize mah={400}>{content}</ScrollArea.Autosize>
+            </Stack>
+        </Modal>
+    );
+}
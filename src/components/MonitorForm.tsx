@@ -1,25 +1,49 @@
-import { ActionIcon, Button, Group, NumberInput, Stack, Switch, Text, TextInput, Tooltip } from "@mantine/core";
+import { ActionIcon, Button, Group, NumberInput, SegmentedControl, Stack, Switch, TagsInput, Text, TextInput, Tooltip } from "@mantine/core";
 import { IconFolder } from "@tabler/icons-react";
 import { useEffect, useState } from "react";
 import { AppColors } from "../theme";
-import { handleFolderBrowse } from "../utils";
+import { api } from "../api";
+import { AlertLevel, GrowthAlert, SizeMode, ThresholdLevel } from "../types";
+import { errorMessage, handleFolderBrowse } from "../utils";
+
+export interface MonitorFormValues {
+    name: string;
+    path: string;
+    threshold: number;
+    maxDepth: number | undefined;
+    enabled: boolean;
+    largestFilesLimit: number | undefined;
+    extensionStats: boolean;
+    ageHistogram: boolean;
+    staleAfterDays: number | undefined;
+    sizeMode: SizeMode;
+    dedupeHardlinks: boolean;
+    followSymlinks: boolean;
+    sameFilesystem: boolean;
+    exclude: string[];
+    include: string[];
+    incremental: boolean;
+    watch: boolean;
+    intervalMinutes: number | undefined;
+    schedule: string;
+    growthAlert: GrowthAlert | undefined;
+    levels: ThresholdLevel[];
+    maxFileCount: number | undefined;
+}
+
+// What monitors without levels of their own get from the backend
+const DEFAULT_LEVELS: ThresholdLevel[] = [
+    { level: "warning", percent: 80, notify: false },
+    { level: "critical", percent: 100, notify: true }
+];
+
+const findLevel = (levels: ThresholdLevel[] | undefined, level: AlertLevel): ThresholdLevel =>
+    levels?.find(l => l.level === level) ?? DEFAULT_LEVELS.find(l => l.level === level)!;
 
 interface MonitorFormProps {
-    initialValues?: {
-        name: string;
-        path: string;
-        threshold: number;
-        maxDepth: number | undefined;
-        enabled: boolean;
-    };
+    initialValues?: MonitorFormValues;
     submitLabel: string;
-    onSubmit: (values: {
-        name: string;
-        path: string;
-        threshold: number;
-        maxDepth: number | undefined;
-        enabled: boolean;
-    }) => void;
+    onSubmit: (values: MonitorFormValues) => void;
     showExtendedFields?: boolean;
 }
 
@@ -29,6 +53,27 @@ export function MonitorForm({ initialValues, submitLabel, onSubmit, showExtended
     const [threshold, setThreshold] = useState<string | number>(initialValues?.threshold || 1024);
     const [maxDepth, setMaxDepth] = useState<string | number>(initialValues?.maxDepth || '');
     const [enabled, setEnabled] = useState(initialValues?.enabled ?? true);
+    const [largestFilesLimit, setLargestFilesLimit] = useState<string | number>(initialValues?.largestFilesLimit || '');
+    const [extensionStats, setExtensionStats] = useState(initialValues?.extensionStats ?? false);
+    const [ageHistogram, setAgeHistogram] = useState(initialValues?.ageHistogram ?? false);
+    const [staleAfterDays, setStaleAfterDays] = useState<string | number>(initialValues?.staleAfterDays || '');
+    const [sizeMode, setSizeMode] = useState<SizeMode>(initialValues?.sizeMode ?? "logical");
+    const [dedupeHardlinks, setDedupeHardlinks] = useState(initialValues?.dedupeHardlinks ?? false);
+    const [followSymlinks, setFollowSymlinks] = useState(initialValues?.followSymlinks ?? false);
+    const [sameFilesystem, setSameFilesystem] = useState(initialValues?.sameFilesystem ?? false);
+    const [exclude, setExclude] = useState<string[]>(initialValues?.exclude ?? []);
+    const [include, setInclude] = useState<string[]>(initialValues?.include ?? []);
+    const [incremental, setIncremental] = useState(initialValues?.incremental ?? false);
+    const [watch, setWatch] = useState(initialValues?.watch ?? false);
+    const [intervalMinutes, setIntervalMinutes] = useState<string | number>(initialValues?.intervalMinutes || '');
+    const [schedule, setSchedule] = useState(initialValues?.schedule ?? '');
+    const [growthMb, setGrowthMb] = useState<string | number>(initialValues?.growthAlert?.mb || '');
+    const [growthHours, setGrowthHours] = useState<string | number>(initialValues?.growthAlert?.hours || 24);
+    const [maxFileCount, setMaxFileCount] = useState<string | number>(initialValues?.maxFileCount || '');
+    const [warning, setWarning] = useState<ThresholdLevel>(findLevel(initialValues?.levels, "warning"));
+    const [critical, setCritical] = useState<ThresholdLevel>(findLevel(initialValues?.levels, "critical"));
+    const [scheduleError, setScheduleError] = useState<string | null>(null);
+    const [scheduleNext, setScheduleNext] = useState<number | null>(null);
 
     useEffect(() => {
         if (initialValues) {
@@ -37,18 +82,71 @@ export function MonitorForm({ initialValues, submitLabel, onSubmit, showExtended
             setThreshold(initialValues.threshold);
             setMaxDepth(initialValues.maxDepth || '');
             setEnabled(initialValues.enabled);
+            setLargestFilesLimit(initialValues.largestFilesLimit || '');
+            setExtensionStats(initialValues.extensionStats);
+            setAgeHistogram(initialValues.ageHistogram);
+            setStaleAfterDays(initialValues.staleAfterDays || '');
+            setSizeMode(initialValues.sizeMode);
+            setDedupeHardlinks(initialValues.dedupeHardlinks);
+            setFollowSymlinks(initialValues.followSymlinks);
+            setSameFilesystem(initialValues.sameFilesystem);
+            setExclude(initialValues.exclude);
+            setInclude(initialValues.include);
+            setIncremental(initialValues.incremental);
+            setWatch(initialValues.watch);
+            setIntervalMinutes(initialValues.intervalMinutes || '');
+            setSchedule(initialValues.schedule);
+            setGrowthMb(initialValues.growthAlert?.mb || '');
+            setGrowthHours(initialValues.growthAlert?.hours || 24);
+            setMaxFileCount(initialValues.maxFileCount || '');
+            setWarning(findLevel(initialValues.levels, "warning"));
+            setCritical(findLevel(initialValues.levels, "critical"));
         }
     }, [initialValues]);
 
+    useEffect(() => {
+        setScheduleError(null);
+        setScheduleNext(null);
+        if (!schedule.trim()) return;
+
+        let current = true;
+        api.checkSchedule(schedule)
+            .then(next => { if (current) setScheduleNext(next); })
+            .catch(e => { if (current) setScheduleError(errorMessage(e)); });
+        return () => { current = false; };
+    }, [schedule]);
+
     const handleSubmit = () => {
-        if (!path) return;
+        if (!path || scheduleError) return;
         const depthValue = maxDepth === '' || maxDepth === 0 ? undefined : Number(maxDepth);
+        const largestValue = largestFilesLimit === '' || largestFilesLimit === 0 ? undefined : Number(largestFilesLimit);
+        const staleValue = staleAfterDays === '' || staleAfterDays === 0 ? undefined : Number(staleAfterDays);
+        const intervalValue = intervalMinutes === '' || intervalMinutes === 0 ? undefined : Number(intervalMinutes);
+        const fileCountValue = maxFileCount === '' || maxFileCount === 0 ? undefined : Number(maxFileCount);
+        const growthAlert = growthMb === '' || growthMb === 0 ? undefined : { mb: Number(growthMb), hours: Number(growthHours) || 24 };
         onSubmit({
             name: name || "New Monitor",
             path,
             threshold: Number(threshold),
             maxDepth: depthValue,
-            enabled
+            enabled,
+            largestFilesLimit: largestValue,
+            extensionStats,
+            ageHistogram,
+            staleAfterDays: staleValue,
+            sizeMode,
+            dedupeHardlinks,
+            followSymlinks,
+            sameFilesystem,
+            exclude,
+            include,
+            incremental,
+            watch,
+            intervalMinutes: intervalValue,
+            schedule: schedule.trim(),
+            growthAlert,
+            maxFileCount: fileCountValue,
+            levels: [warning, critical].map(l => ({ ...l, message: l.message?.trim() || undefined }))
         });
     };
 
@@ -96,7 +194,203 @@ export function MonitorForm({ initialValues, submitLabel, onSubmit, showExtended
                             placeholder="Unlimited"
                         />
                     </Tooltip>
+                    <NumberInput
+                        label="Scan Every (minutes)"
+                        description="How often background checks scan this folder. 0 = app setting"
+                        value={intervalMinutes}
+                        onChange={setIntervalMinutes}
+                        min={0}
+                        max={10080}
+                        placeholder="App setting"
+                        disabled={!!schedule.trim()}
+                    />
+                    <TextInput
+                        label="Schedule"
+                        description={scheduleNext
+                            ? `Next run ${new Date(scheduleNext * 1000).toLocaleString()}`
+                            : "Cron expression, e.g. 0 3 * * * for 3 AM daily. Overrides the interval"}
+                        placeholder="Use interval"
+                        value={schedule}
+                        onChange={(e) => setSchedule(e.currentTarget.value)}
+                        error={scheduleError}
+                    />
+                    <Group justify="space-between" align="center">
+                        <div>
+                            <Text size="sm">Size Mode</Text>
+                            <Text size="xs" c="dimmed">What the threshold is compared against</Text>
+                        </div>
+                        <SegmentedControl
+                            size="xs"
+                            value={sizeMode}
+                            onChange={(v) => setSizeMode(v as SizeMode)}
+                            data={[{ value: 'logical', label: 'File size' }, { value: 'allocated', label: 'On disk' }]}
+                        />
+                    </Group>
+                    <TagsInput
+                        label="Exclude"
+                        description="Skip matching files and folders, e.g. node_modules, *.tmp, cache/big"
+                        value={exclude}
+                        onChange={setExclude}
+                        placeholder="Add pattern..."
+                        clearable
+                    />
+                    <TagsInput
+                        label="Only Include"
+                        description="Count only matching files, e.g. *.log. Empty = all files"
+                        value={include}
+                        onChange={setInclude}
+                        placeholder="Add pattern..."
+                        clearable
+                    />
+                    <NumberInput
+                        label="Largest Files"
+                        description="Keep a list of the N largest files after each scan. 0 = off"
+                        value={largestFilesLimit}
+                        onChange={setLargestFilesLimit}
+                        min={0}
+                        max={500}
+                        placeholder="Off"
+                    />
+                    {([["Warning", warning, setWarning], ["Critical", critical, setCritical]] as const).map(([label, value, setValue]) => (
+                        <Stack key={label} gap={4}>
+                            <Group grow align="flex-end">
+                                <NumberInput
+                                    label={`${label} At (%)`}
+                                    description="Percent of the threshold"
+                                    value={value.percent}
+                                    onChange={(v) => setValue({ ...value, percent: Number(v) || 0 })}
+                                    min={1}
+                                    max={1000}
+                                />
+                                <TextInput
+                                    label="Message"
+                                    description="Replaces the notification text"
+                                    placeholder="Default"
+                                    value={value.message ?? ''}
+                                    onChange={(e) => setValue({ ...value, message: e.currentTarget.value })}
+                                />
+                            </Group>
+                            <Switch
+                                size="xs"
+                                label={`Notify on ${label.toLowerCase()}`}
+                                checked={value.notify}
+                                onChange={(e) => setValue({ ...value, notify: e.currentTarget.checked })}
+                            />
+                        </Stack>
+                    ))}
+                    <NumberInput
+                        label="Max Files"
+                        description="Notify when the folder holds more files than this. 0 = off"
+                        value={maxFileCount}
+                        onChange={setMaxFileCount}
+                        min={0}
+                        thousandSeparator=","
+                        placeholder="Off"
+                    />
+                    <Group grow align="flex-start">
+                        <NumberInput
+                            label="Growth Alert (MB)"
+                            description="Notify when it grows more than this. 0 = off"
+                            value={growthMb}
+                            onChange={setGrowthMb}
+                            min={0}
+                            placeholder="Off"
+                        />
+                        <NumberInput
+                            label="Within (hours)"
+                            description="Time window for the growth alert"
+                            value={growthHours}
+                            onChange={setGrowthHours}
+                            min={1}
+                            max={24 * 30}
+                            disabled={growthMb === '' || growthMb === 0}
+                        />
+                    </Group>
+                    <NumberInput
+                        label="Stale After (days)"
+                        description="Report how much data hasn't been modified in this many days. 0 = off"
+                        value={staleAfterDays}
+                        onChange={setStaleAfterDays}
+                        min={0}
+                        max={3650}
+                        placeholder="Off"
+                    />
                     <Group justify="space-between" align="center" mt="xs">
+                        <div>
+                            <Text size="sm">File type breakdown</Text>
+                            <Text size="xs" c="dimmed">Total size per file extension on each scan</Text>
+                        </div>
+                        <Switch
+                            checked={extensionStats}
+                            onChange={(e) => setExtensionStats(e.currentTarget.checked)}
+                        />
+                    </Group>
+                    <Group justify="space-between" align="center">
+                        <div>
+                            <Text size="sm">File age breakdown</Text>
+                            <Text size="xs" c="dimmed">Group size by last modified time on each scan</Text>
+                        </div>
+                        <Switch
+                            checked={ageHistogram}
+                            onChange={(e) => setAgeHistogram(e.currentTarget.checked)}
+                        />
+                    </Group>
+                    <Group justify="space-between" align="center">
+                        <div>
+                            <Text size="sm">Count hard links once</Text>
+                            <Text size="xs" c="dimmed">Skip extra links to a file already counted (slower on Windows)</Text>
+                        </div>
+                        <Switch
+                            checked={dedupeHardlinks}
+                            onChange={(e) => setDedupeHardlinks(e.currentTarget.checked)}
+                        />
+                    </Group>
+                    <Group justify="space-between" align="center">
+                        <div>
+                            <Text size="sm">Follow links</Text>
+                            <Text size="xs" c="dimmed">Include the targets of symlinks and junctions</Text>
+                        </div>
+                        <Switch
+                            checked={followSymlinks}
+                            onChange={(e) => setFollowSymlinks(e.currentTarget.checked)}
+                        />
+                    </Group>
+                    <Group justify="space-between" align="center">
+                        <div>
+                            <Text size="sm">Stay on this drive</Text>
+                            <Text size="xs" c="dimmed">Skip folders on other drives, network shares and mounts</Text>
+                        </div>
+                        <Switch
+                            checked={sameFilesystem}
+                            onChange={(e) => setSameFilesystem(e.currentTarget.checked)}
+                        />
+                    </Group>
+                    <Group justify="space-between" align="center">
+                        <div>
+                            <Text size="sm">Quick background scans</Text>
+                            <Text size="xs" c="dimmed">Only re-read changed folders; Scan Now always does a full scan</Text>
+                        </div>
+                        <Switch
+                            checked={incremental}
+                            onChange={(e) => setIncremental(e.currentTarget.checked)}
+                        />
+                    </Group>
+                    <Group justify="space-between" align="center">
+                        <div>
+                            <Text size="sm">Live updates</Text>
+                            <Text size="xs" c="dimmed">
+                                {dedupeHardlinks
+                                    ? "Not available while hard links are counted once"
+                                    : "Update the size as files change, between scans"}
+                            </Text>
+                        </div>
+                        <Switch
+                            checked={watch && !dedupeHardlinks}
+                            disabled={dedupeHardlinks}
+                            onChange={(e) => setWatch(e.currentTarget.checked)}
+                        />
+                    </Group>
+                    <Group justify="space-between" align="center">
                         <div>
                             <Text size="sm">Enabled</Text>
                             <Text size="xs" c="dimmed">Include this folder in scans</Text>
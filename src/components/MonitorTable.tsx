@@ -30,13 +30,19 @@ import {
     IconChevronUp,
     IconBell,
     IconBellOff,
+    IconBellZ,
+    IconBellCheck,
     IconStack,
     IconBan,
-    IconRefresh
+    IconRefresh,
+    IconPlayerStop,
+    IconChartTreemap,
+    IconFiles,
+    IconEye
 } from "@tabler/icons-react";
-import { MonitorStatus } from "../types";
+import { Alert, MonitorStatus } from "../types";
 import { AppColors, getStatusColor } from "../theme";
-import { formatBytes, formatRelativeTime, formatFileCount } from "../utils";
+import { formatBytes, formatRelativeTime, formatFileCount, levelFromSize } from "../utils";
 import { api } from "../api";
 
 
@@ -91,6 +97,7 @@ function Th({ children, reversed, sorted, onSort, width, align }: ThProps) {
 
 interface MonitorTableProps {
     data: MonitorStatus[];
+    alerts: Record<string, Alert>;
     sortBy: string | null;
     reverseSortDirection: boolean;
     onSort: (field: string) => void;
@@ -100,12 +107,17 @@ interface MonitorTableProps {
     onToggleNotify: (id: string) => void;
     onToggleEnabled: (id: string) => void;
     onScanOne: (monitor: MonitorStatus) => void;
+    onShowBreakdown: (monitor: MonitorStatus) => void;
+    onShowLargestFiles: (monitor: MonitorStatus) => void;
+    onSnoozeAlert: (id: string, hours: number) => void;
+    onAcknowledgeAlert: (id: string) => void;
 }
 
 
 
 export function MonitorTable({
     data,
+    alerts,
     sortBy,
     reverseSortDirection,
     onSort,
@@ -115,6 +127,10 @@ export function MonitorTable({
     onToggleNotify,
     onToggleEnabled,
     onScanOne,
+    onShowBreakdown,
+    onShowLargestFiles,
+    onSnoozeAlert,
+    onAcknowledgeAlert,
 }: Readonly<MonitorTableProps>) {
     return (
         <div style={{ flex: 1, minHeight: 0, display: 'flex', flexDirection: 'column' }}>
@@ -186,7 +202,14 @@ export function MonitorTable({
                             const isNotFound = m.error === "Path not found";
                             const currentMB = (m.currentSizeBytes || 0) / (1024 * 1024);
                             const percentage = Math.min(100, (currentMB / m.threshold) * 100);
-                            const color = getStatusColor(m.enabled, m.loading || false, m.error, currentMB, m.threshold);
+                            const level = m.level ?? levelFromSize(currentMB, m.threshold);
+                            const color = getStatusColor(m.enabled, m.loading || false, m.error, level);
+                            const alert = alerts[m.id];
+                            const conditions = alert ? [alert, alert.growth, alert.fileCount] : [];
+                            const alertActive = conditions.some(c => c?.state === "firing" || c?.state === "acknowledged");
+                            const alertFiring = conditions.some(c => c?.state === "firing");
+                            const acknowledged = alertActive && !alertFiring;
+                            const snoozedUntil = alert?.snoozedUntil && alert.snoozedUntil * 1000 > Date.now() ? alert.snoozedUntil : null;
 
                             let statusIcon = <IconCheck size={12} />;
                             if (!m.enabled) {
@@ -195,9 +218,7 @@ export function MonitorTable({
                                 statusIcon = <Loader size={10} />;
                             } else if (m.error) {
                                 statusIcon = <IconX size={12} />;
-                            } else if (currentMB > m.threshold) {
-                                statusIcon = <IconAlertTriangle size={12} />;
-                            } else if (currentMB > m.threshold * 0.8) {
+                            } else if (level !== "ok") {
                                 statusIcon = <IconAlertTriangle size={12} />;
                             }
 
@@ -247,19 +268,60 @@ export function MonitorTable({
                                                     <Badge size="xs" variant="light" color={AppColors.info} leftSection={<IconStack size={10} />}>D:{m.maxDepth}</Badge>
                                                 </Tooltip>
                                             )}
+                                            {m.watch && m.enabled && !m.dedupeHardlinks && (
+                                                <Tooltip label="Live: size updates as files change">
+                                                    <Badge size="xs" variant="light" color={AppColors.success} leftSection={<IconEye size={10} />}>Live</Badge>
+                                                </Tooltip>
+                                            )}
+                                            {m.partial && (
+                                                <Tooltip
+                                                    multiline
+                                                    w={360}
+                                                    label={
+                                                        <>
+                                                            <div>{(m.skippedCount ?? 0).toLocaleString()} entries couldn't be read, so the size may be low.</div>
+                                                            {m.skippedSamples?.slice(0, 5).map(s => (
+                                                                <div key={s.path} style={{ fontFamily: 'monospace', fontSize: '0.8em' }}>{s.path}: {s.error}</div>
+                                                            ))}
+                                                        </>
+                                                    }
+                                                >
+                                                    <Badge size="xs" variant="light" color={AppColors.warning} leftSection={<IconAlertTriangle size={10} />}>Partial</Badge>
+                                                </Tooltip>
+                                            )}
                                         </Group>
                                     </Table.Td>
                                     <Table.Td p="xs">
                                         {isNotFound ? (
                                             <Badge color={AppColors.danger} variant="light" size="xs">Not Found</Badge>
                                         ) : (
-                                            <Tooltip label={`${percentage.toFixed(0)}%`} withArrow>
+                                            <Tooltip
+                                                withArrow
+                                                label={
+                                                    <>
+                                                        <div>{percentage.toFixed(0)}%</div>
+                                                        {m.growthBytesPerDay != null && Math.abs(m.growthBytesPerDay) >= 1024 * 1024 && (
+                                                            <div>{m.growthBytesPerDay > 0 ? "Growing" : "Shrinking"} {formatBytes(Math.abs(m.growthBytesPerDay))}/day</div>
+                                                        )}
+                                                        {m.thresholdEta && (
+                                                            <div>Reaches limit around {new Date(m.thresholdEta * 1000).toLocaleDateString()}</div>
+                                                        )}
+                                                    </>
+                                                }
+                                            >
                                                 <Progress value={percentage} color={color} size="sm" h={6} radius="xl" animated={m.loading} />
                                             </Tooltip>
                                         )}
                                     </Table.Td>
                                     <Table.Td p="xs">
-                                        <Tooltip label={m.loading ? "Scan in progress..." : (m.lastScanAt ? new Date(m.lastScanAt * 1000).toLocaleString() : "Never scanned")}>
+                                        <Tooltip label={m.loading ? "Scan in progress..." : (
+                                            <>
+                                                <div>{m.lastScanAt ? new Date(m.lastScanAt * 1000).toLocaleString() : "Never scanned"}</div>
+                                                {m.enabled && m.nextScanAt && (
+                                                    <div>Next scan {new Date(m.nextScanAt * 1000).toLocaleString()}</div>
+                                                )}
+                                            </>
+                                        )}>
                                             <Text size="xs" c={m.loading ? AppColors.primary : "dimmed"} fw={m.loading ? 700 : 400} style={{ whiteSpace: 'nowrap' }}>
                                                 {m.loading ? "Scanning..." : formatRelativeTime(m.lastScanAt)}
                                             </Text>
@@ -271,39 +333,98 @@ export function MonitorTable({
                                         ) : (
                                             <div style={{ textAlign: 'right' }}>
                                                 <Text size="sm" style={{ whiteSpace: 'nowrap', fontVariantNumeric: 'tabular-nums' }}>
-                                                    <span style={{ fontWeight: 700 }}>{formatBytes(m.currentSizeBytes)}</span>
+                                                    <Tooltip
+                                                        disabled={!m.extensions?.length && m.allocatedBytes == null && !m.hardlinkBytesSaved}
+                                                        multiline
+                                                        label={
+                                                            <>
+                                                                {m.logicalBytes != null && m.allocatedBytes != null && (
+                                                                    <div>File size: {formatBytes(m.logicalBytes)} / On disk: {formatBytes(m.allocatedBytes)}</div>
+                                                                )}
+                                                                {!!m.hardlinkBytesSaved && (
+                                                                    <div>Hard links counted once: {formatBytes(m.hardlinkBytesSaved)} saved</div>
+                                                                )}
+                                                                {m.extensions?.slice(0, 6).map(e => (
+                                                                    <div key={e.extension}>{e.extension}: {formatBytes(e.sizeBytes)} ({formatFileCount(e.fileCount)})</div>
+                                                                ))}
+                                                            </>
+                                                        }
+                                                    >
+                                                        <span style={{ fontWeight: 700 }}>{formatBytes(m.currentSizeBytes)}</span>
+                                                    </Tooltip>
                                                     {m.fileCount !== undefined && m.fileCount > 0 && (
                                                         <>
                                                             <span style={{ margin: '0 6px', color: 'var(--mantine-color-dimmed)', opacity: 0.3 }}>|</span>
-                                                            <Tooltip label={`${m.fileCount.toLocaleString()} files`}>
-                                                                <span style={{ fontSize: '0.85em', color: 'var(--mantine-color-dimmed)', cursor: 'help' }}>
+                                                            <Tooltip label={m.maxFileCount
+                                                                ? `${m.fileCount.toLocaleString()} of ${m.maxFileCount.toLocaleString()} files allowed`
+                                                                : `${m.fileCount.toLocaleString()} files`}>
+                                                                <span style={{
+                                                                    fontSize: '0.85em',
+                                                                    color: m.maxFileCount && m.fileCount > m.maxFileCount ? `var(--mantine-color-${AppColors.danger}-filled)` : 'var(--mantine-color-dimmed)',
+                                                                    cursor: 'help'
+                                                                }}>
                                                                     {formatFileCount(m.fileCount)}
                                                                 </span>
                                                             </Tooltip>
                                                         </>
                                                     )}
                                                 </Text>
+                                                {(m.staleBytes != null || !!m.ageBuckets?.length) && (
+                                                    <Tooltip
+                                                        disabled={!m.ageBuckets?.length}
+                                                        multiline
+                                                        label={m.ageBuckets?.map(b => (
+                                                            <div key={b.label}>{b.label}: {formatBytes(b.sizeBytes)} ({formatFileCount(b.fileCount)})</div>
+                                                        ))}
+                                                    >
+                                                        <Text size="xs" c="dimmed" style={{ whiteSpace: 'nowrap', cursor: 'help' }}>
+                                                            {m.staleBytes != null && m.staleAfterDays
+                                                                ? `${formatBytes(m.staleBytes)} untouched ${m.staleAfterDays}d+`
+                                                                : "By age"}
+                                                        </Text>
+                                                    </Tooltip>
+                                                )}
                                             </div>
                                         )}
                                     </Table.Td>
                                     <Table.Td p="xs">
-                                        <Tooltip label="Scan Now">
-                                            <ActionIcon
-                                                variant="subtle"
-                                                color={AppColors.primary}
-                                                size="sm"
-                                                loading={m.loading}
-                                                onClick={() => onScanOne(m)}
-                                                disabled={!m.enabled}
-                                            >
-                                                <IconRefresh size={14} />
-                                            </ActionIcon>
-                                        </Tooltip>
+                                        {m.loading ? (
+                                            <Tooltip label="Cancel Scan">
+                                                <ActionIcon
+                                                    variant="subtle"
+                                                    color={AppColors.danger}
+                                                    size="sm"
+                                                    onClick={() => api.cancelScan(m.id)}
+                                                >
+                                                    <IconPlayerStop size={14} />
+                                                </ActionIcon>
+                                            </Tooltip>
+                                        ) : (
+                                            <Tooltip label="Scan Now">
+                                                <ActionIcon
+                                                    variant="subtle"
+                                                    color={AppColors.primary}
+                                                    size="sm"
+                                                    onClick={() => onScanOne(m)}
+                                                    disabled={!m.enabled}
+                                                >
+                                                    <IconRefresh size={14} />
+                                                </ActionIcon>
+                                            </Tooltip>
+                                        )}
                                     </Table.Td>
                                     <Table.Td p="xs">
-                                        <Tooltip label={m.notify ? "Notifications ON" : "Notifications OFF"}>
+                                        <Tooltip label={!m.notify
+                                            ? "Notifications OFF"
+                                            : snoozedUntil
+                                                ? `Snoozed until ${new Date(snoozedUntil * 1000).toLocaleString()}`
+                                                : acknowledged ? "Alert acknowledged" : "Notifications ON"}>
                                             <ActionIcon variant="subtle" color={m.notify ? AppColors.warning : AppColors.neutral} size="sm" onClick={() => onToggleNotify(m.id)}>
-                                                {m.notify ? <IconBell size={14} /> : <IconBellOff size={14} />}
+                                                {!m.notify
+                                                    ? <IconBellOff size={14} />
+                                                    : snoozedUntil
+                                                        ? <IconBellZ size={14} />
+                                                        : acknowledged ? <IconBellCheck size={14} /> : <IconBell size={14} />}
                                             </ActionIcon>
                                         </Tooltip>
                                     </Table.Td>
@@ -312,6 +433,20 @@ export function MonitorTable({
                                             <Menu.Target><ActionIcon variant="subtle" color={AppColors.neutral} size="sm"><IconDots size={14} /></ActionIcon></Menu.Target>
                                             <Menu.Dropdown>
                                                 <Menu.Item leftSection={<IconEdit size={14} />} onClick={() => startEdit(m)}>Edit Settings</Menu.Item>
+                                                <Menu.Item
+                                                    leftSection={<IconChartTreemap size={14} />}
+                                                    onClick={() => onShowBreakdown(m)}
+                                                    disabled={!m.enabled || isNotFound}
+                                                >
+                                                    Size Breakdown
+                                                </Menu.Item>
+                                                <Menu.Item
+                                                    leftSection={<IconFiles size={14} />}
+                                                    onClick={() => onShowLargestFiles(m)}
+                                                    disabled={!m.enabled || isNotFound}
+                                                >
+                                                    Largest Files
+                                                </Menu.Item>
                                                 <Menu.Item
                                                     leftSection={<IconBell size={14} />}
                                                     onClick={() => api.testNotification(m.id, m.name, m.path, currentMB, m.threshold)}
@@ -319,6 +454,20 @@ export function MonitorTable({
                                                 >
                                                     Test Notification
                                                 </Menu.Item>
+                                                {alertActive && (
+                                                    <>
+                                                        <Menu.Divider />
+                                                        <Menu.Item
+                                                            leftSection={<IconBellCheck size={14} />}
+                                                            onClick={() => onAcknowledgeAlert(m.id)}
+                                                            disabled={!alertFiring}
+                                                        >
+                                                            Acknowledge Alert
+                                                        </Menu.Item>
+                                                        <Menu.Item leftSection={<IconBellZ size={14} />} onClick={() => onSnoozeAlert(m.id, 1)}>Snooze 1 Hour</Menu.Item>
+                                                        <Menu.Item leftSection={<IconBellZ size={14} />} onClick={() => onSnoozeAlert(m.id, 24)}>Snooze 1 Day</Menu.Item>
+                                                    </>
+                                                )}
                                                 <Menu.Divider />
                                                 <Menu.Item color={AppColors.danger} leftSection={<IconTrash size={14} />} onClick={() => removeMonitor(m.id)}>Stop Tracking</Menu.Item>
                                             </Menu.Dropdown>
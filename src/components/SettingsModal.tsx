@@ -5,6 +5,7 @@ import { save, open, confirm } from '@tauri-apps/plugin-dialog';
 import { AppSettings } from "../types";
 import { AppColors } from "../theme";
 import { api } from "../api";
+import { errorMessage } from "../utils";
 
 interface SettingsModalProps {
     readonly opened: boolean;
@@ -40,7 +41,7 @@ export function SettingsModal({ opened, settings, onUpdateSettings, onClose, onR
             console.error(error);
             notifications.show({
                 title: "Export Failed",
-                message: String(error),
+                message: errorMessage(error),
                 color: "red",
                 icon: <IconX size={16} />
             });
@@ -67,7 +68,7 @@ export function SettingsModal({ opened, settings, onUpdateSettings, onClose, onR
             console.error(error);
             notifications.show({
                 title: "Import Failed",
-                message: String(error),
+                message: errorMessage(error),
                 color: "red",
                 icon: <IconX size={16} />
             });
@@ -131,6 +132,50 @@ export function SettingsModal({ opened, settings, onUpdateSettings, onClose, onR
                             />
                         )}
                     </Group>
+                    <Group justify="space-between" align="center">
+                        <div>
+                            <Text size="sm">Scan threads</Text>
+                            <Text size="xs" c="dimmed">Parallel workers per folder scan. 0 = one per CPU core.</Text>
+                        </div>
+                        <NumberInput
+                            size="xs"
+                            w={70}
+                            min={0}
+                            max={64}
+                            value={settings.scanWorkers}
+                            onChange={(value) => onUpdateSettings({ ...settings, scanWorkers: Number(value) || 0 })}
+                        />
+                    </Group>
+                    <Group justify="space-between" align="center">
+                        <div>
+                            <Text size="sm">Remind while over limit</Text>
+                            <Text size="xs" c="dimmed">Repeat an alert until it is acknowledged. 0 = never.</Text>
+                        </div>
+                        <NumberInput
+                            size="xs"
+                            w={80}
+                            min={0}
+                            max={720}
+                            suffix=" h"
+                            value={settings.renotifyMinutes / 60}
+                            onChange={(value) => onUpdateSettings({ ...settings, renotifyMinutes: Math.round((Number(value) || 0) * 60) })}
+                        />
+                    </Group>
+                    <Group justify="space-between" align="center">
+                        <div>
+                            <Text size="sm">Alert clear margin</Text>
+                            <Text size="xs" c="dimmed">How far below a level a folder must drop before its alert clears.</Text>
+                        </div>
+                        <NumberInput
+                            size="xs"
+                            w={70}
+                            min={0}
+                            max={50}
+                            suffix="%"
+                            value={settings.hysteresisPercent}
+                            onChange={(value) => onUpdateSettings({ ...settings, hysteresisPercent: Number(value) || 0 })}
+                        />
+                    </Group>
                 </Stack>
 
                 <Divider />
@@ -0,0 +1,86 @@
+import { useEffect, useState } from "react";
+import { Modal, Stack, Group, Text, Progress, Loader, Center, SegmentedControl, ScrollArea } from "@mantine/core";
+import { MonitorStatus, SizeNode } from "../types";
+import { AppColors } from "../theme";
+import { errorMessage, formatBytes, formatFileCount } from "../utils";
+import { api } from "../api";
+
+interface SizeBreakdownModalProps {
+    readonly opened: boolean;
+    readonly onClose: () => void;
+    readonly monitor: MonitorStatus | null;
+}
+
+function BreakdownRow({ node, parentSize, level }: Readonly<{ node: SizeNode; parentSize: number; level: number }>) {
+    const percentage = parentSize > 0 ? (node.sizeBytes / parentSize) * 100 : 0;
+
+    return (
+        <>
+            <Group gap="xs" wrap="nowrap" pl={level * 16}>
+                <Text size="sm" style={{ flex: 1, minWidth: 0 }} truncate="end" title={node.path}>{node.name}</Text>
+                <Progress value={percentage} color={AppColors.primary} size="sm" w={80} radius="xl" />
+                <Text size="xs" c="dimmed" w={50} ta="right">{formatFileCount(node.fileCount)}</Text>
+                <Text size="sm" fw={600} w={80} ta="right" style={{ fontVariantNumeric: 'tabular-nums' }}>{formatBytes(node.sizeBytes)}</Text>
+            </Group>
+            {node.children.map(child => (
+                <BreakdownRow key={child.path} node={child} parentSize={node.sizeBytes} level={level + 1} />
+            ))}
+        </>
+    );
+}
+
+export function SizeBreakdownModal({ opened, onClose, monitor }: Readonly<SizeBreakdownModalProps>) {
+    const [depth, setDepth] = useState("1");
+    const [tree, setTree] = useState<SizeNode | null>(null);
+    const [error, setError] = useState<string | null>(null);
+
+    useEffect(() => {
+        if (!opened || !monitor) return;
+        let active = true;
+        let pending = true;
+        setTree(null);
+        setError(null);
+        api.getSizeBreakdown(monitor.id, Number(depth))
+            .then(result => { if (active) setTree(result); })
+            .catch(e => { if (active) setError(errorMessage(e)); })
+            .finally(() => { pending = false; });
+        return () => {
+            active = false;
+            // Stop the walk if the modal is closed before it finishes
+            if (pending) api.cancelScan(monitor.id);
+        };
+    }, [opened, monitor, depth]);
+
+    let content = <Center py="xl"><Loader size="sm" /></Center>;
+    if (error) {
+        content = <Text size="sm" c={AppColors.danger}>{error}</Text>;
+    } else if (tree) {
+        content = tree.children.length === 0 ? (
+            <Text size="sm" c="dimmed">No subfolders contain files.</Text>
+        ) : (
+            <Stack gap={4}>
+                {tree.children.map(child => (
+                    <BreakdownRow key={child.path} node={child} parentSize={tree.sizeBytes} level={0} />
+                ))}
+            </Stack>
+        );
+    }
+
+    return (
+        <Modal opened={opened} onClose={onClose} title={`Size Breakdown${monitor ? ` - ${monitor.name}` : ''}`} size="lg" centered>
+            <Stack>
+                <Group justify="space-between">
+                    <Text size="sm" c="dimmed">{tree ? `${formatBytes(tree.sizeBytes)} in ${formatFileCount(tree.fileCount)} files` : "Scanning..."}</Text>
+                    <SegmentedControl
+                        size="xs"
+                        value={depth}
+                        onChange={setDepth}
+                        disabled={!tree && !error}
+                        data={[{ value: '1', label: '1 level' }, { value: '2', label: '2 levels' }, { value: '3', label: '3 levels' }]}
+                    />
+                </Group>
+                <ScrollArea.Autosize mah={400}>{content}</ScrollArea.Autosize>
+            </Stack>
+        </Modal>
+    );
+}
@@ -1,4 +1,4 @@
-import { Group, Text, rem, Anchor } from "@mantine/core";
+import { Group, Text, rem, Anchor, Menu } from "@mantine/core";
 import {
     IconFolder,
     IconHistory,
@@ -6,20 +6,28 @@ import {
     IconCircleCheck,
     IconLoader2,
     IconBug,
-    IconUser
+    IconUser,
+    IconPlayerPause,
+    IconPlayerPlay,
+    IconRefresh
 } from "@tabler/icons-react";
 import { getVersion } from '@tauri-apps/api/app';
 import { useEffect, useState } from 'react';
 import { AppColors } from "../theme";
 import { formatRelativeTime } from "../utils";
+import { WorkerStatus } from "../types";
 
 interface StatusBarProps {
     readonly locationCount: number;
     readonly lastAutoCheck: number | null;
     readonly isScanning: boolean;
+    readonly workerStatus: WorkerStatus;
+    readonly onCheckNow: () => void;
+    readonly onPause: (minutes?: number) => void;
+    readonly onResume: () => void;
 }
 
-export function StatusBar({ locationCount, lastAutoCheck, isScanning }: StatusBarProps) {
+export function StatusBar({ locationCount, lastAutoCheck, isScanning, workerStatus, onCheckNow, onPause, onResume }: StatusBarProps) {
     const [version, setVersion] = useState('');
 
     useEffect(() => {
@@ -61,6 +69,43 @@ export function StatusBar({ locationCount, lastAutoCheck, isScanning }: StatusBa
                     </Text>
                 </Group>
 
+                <Menu shadow="md" width={180} position="top-start">
+                    <Menu.Target>
+                        <Group gap={6} style={{ cursor: 'pointer' }}>
+                            {workerStatus.paused ? <IconPlayerPause size={13} /> : <IconPlayerPlay size={13} />}
+                            <Text size="10.5px" fw={600} tt="uppercase">
+                                {!workerStatus.paused
+                                    ? 'Monitoring'
+                                    : workerStatus.pausedUntil
+                                        ? `Paused until ${new Date(workerStatus.pausedUntil * 1000).toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' })}`
+                                        : 'Paused'}
+                            </Text>
+                        </Group>
+                    </Menu.Target>
+                    <Menu.Dropdown>
+                        <Menu.Item leftSection={<IconRefresh size={14} />} onClick={onCheckNow}>
+                            Scan now
+                        </Menu.Item>
+                        {workerStatus.paused ? (
+                            <Menu.Item leftSection={<IconPlayerPlay size={14} />} onClick={onResume}>
+                                Resume
+                            </Menu.Item>
+                        ) : (
+                            <>
+                                <Menu.Item leftSection={<IconPlayerPause size={14} />} onClick={() => onPause(60)}>
+                                    Pause for 1 hour
+                                </Menu.Item>
+                                <Menu.Item leftSection={<IconPlayerPause size={14} />} onClick={() => onPause(8 * 60)}>
+                                    Pause for 8 hours
+                                </Menu.Item>
+                                <Menu.Item leftSection={<IconPlayerPause size={14} />} onClick={() => onPause()}>
+                                    Pause until resumed
+                                </Menu.Item>
+                            </>
+                        )}
+                    </Menu.Dropdown>
+                </Menu>
+
                 {lastAutoCheck && (
                     <Group gap={6} style={{ cursor: 'default', opacity: 0.8 }}>
                         <IconHistory size={13} />
@@ -1,17 +1,29 @@
 import { useState, useEffect, useMemo, useCallback, useRef } from "react";
 import { listen } from "@tauri-apps/api/event";
 import { confirm } from "@tauri-apps/plugin-dialog";
-import { MonitorConfig, MonitorStatus, AppSettings, ScanProgress } from "../types";
+import { MonitorConfig, MonitorStatus, AppSettings, ScanProgress, WorkerStatus, MistError, Alert, WatchError } from "../types";
 import { api } from "../api";
-import { updateMonitorWithProgress, configToStatus, generateId, isDuplicatePath, calculateStats, mergeMonitors } from "../utils";
+import { errorMessage, updateMonitorWithProgress, configToStatus, generateId, isDuplicatePath, calculateStats, mergeMonitors } from "../utils";
 import { notifications } from "@mantine/notifications";
 import { IconCheck, IconX, IconAlertTriangle } from "@tabler/icons-react";
+import type { MonitorFormValues } from "../components/MonitorForm";
+
+function showStorageError(message: string) {
+    notifications.show({
+        title: "Couldn't Save Changes",
+        message,
+        color: "red",
+        icon: <IconX size={16} />
+    });
+}
 
 export function useMonitors() {
     const [monitors, setMonitors] = useState<MonitorStatus[]>([]);
     const [scanning, setScanning] = useState(false);
-    const [settings, setSettings] = useState<AppSettings>({ minimizeToTray: true, checkIntervalMinutes: 60 });
+    const [settings, setSettings] = useState<AppSettings>({ minimizeToTray: true, checkIntervalMinutes: 60, scanWorkers: 0, renotifyMinutes: 1440, hysteresisPercent: 5 });
     const [lastAutoCheck, setLastAutoCheck] = useState<number | null>(null);
+    const [workerStatus, setWorkerStatus] = useState<WorkerStatus>({ paused: false });
+    const [alerts, setAlerts] = useState<Record<string, Alert>>({});
 
     const scanOneStreaming = useCallback((monitor: MonitorConfig) => {
         setMonitors(prev => prev.map(m => m.id === monitor.id ? { ...m, loading: true, error: null } : m));
@@ -90,6 +102,7 @@ export function useMonitors() {
             await api.saveMonitors(newMonitors);
         } catch (e) {
             console.error("Failed to save", e);
+            showStorageError(errorMessage(e));
         }
     }, []);
 
@@ -99,7 +112,7 @@ export function useMonitors() {
 
     const handleUpdateSettings = useCallback((newSettings: AppSettings) => {
         setSettings(newSettings);
-        api.saveSettings(newSettings);
+        api.saveSettings(newSettings).catch(e => showStorageError(errorMessage(e)));
     }, []);
 
     const handleAdd = useCallback((name: string, path: string, threshold: number) => {
@@ -131,8 +144,8 @@ export function useMonitors() {
         return true;
     }, [monitors, saveToRust, scanOneStreaming]);
 
-    const handleEditSave = useCallback((id: string, name: string, path: string, threshold: number, maxDepth: number | undefined, enabled: boolean) => {
-        if (isDuplicatePath(monitors, path, id)) {
+    const handleEditSave = useCallback((id: string, values: MonitorFormValues) => {
+        if (isDuplicatePath(monitors, values.path, id)) {
             notifications.show({
                 title: "Path Already Monitored",
                 message: "Another monitor item is already using this folder path.",
@@ -144,14 +157,15 @@ export function useMonitors() {
 
         const updated = monitors.map(m => {
             if (m.id === id) {
+                const schedule = values.schedule || undefined;
+                // Work out the next background scan again from the new timing
+                const rescheduled = values.intervalMinutes !== m.intervalMinutes || schedule !== m.schedule;
                 return {
                     ...m,
-                    name,
-                    path,
-                    threshold,
-                    maxDepth,
-                    enabled,
-                    loading: enabled
+                    ...values,
+                    schedule,
+                    nextScanAt: rescheduled ? undefined : m.nextScanAt,
+                    loading: values.enabled
                 };
             }
             return m;
@@ -159,7 +173,7 @@ export function useMonitors() {
         setMonitors(updated);
         saveToRust(updated);
 
-        if (enabled) {
+        if (values.enabled) {
             const idx = updated.findIndex(m => m.id === id);
             if (idx !== -1) scanOneStreaming(updated[idx]);
         }
@@ -222,7 +236,7 @@ export function useMonitors() {
             console.error(e);
             notifications.show({
                 title: "Restore Failed",
-                message: String(e),
+                message: errorMessage(e),
                 color: "red",
                 icon: <IconX size={16} />
             });
@@ -261,6 +275,32 @@ export function useMonitors() {
             fetchMonitors();
         });
 
+        const unlistenStorageError = listen<MistError>("storage-error", (event) => {
+            showStorageError(errorMessage(event.payload));
+        });
+
+        const unlistenSettings = listen("settings-updated", () => {
+            api.getSettings().then(setSettings).catch(console.error);
+        });
+
+        const unlistenWorker = listen<WorkerStatus>("worker-status", (event) => {
+            setWorkerStatus(event.payload);
+        });
+
+        const unlistenAlerts = listen<Record<string, Alert>>("alerts-updated", (event) => {
+            setAlerts(event.payload);
+        });
+
+        const unlistenWatchError = listen<WatchError>("watch-error", (event) => {
+            const name = monitorsRef.current.find(m => m.id === event.payload.monitorId)?.name || "folder";
+            notifications.show({
+                title: "Live Updates Stopped",
+                message: `Couldn't watch ${name}: ${event.payload.error}`,
+                color: "red",
+                icon: <IconX size={16} />
+            });
+        });
+
         const unlistenDragDrop = listen<{ paths: string[] }>("tauri://drag-drop", async (event) => {
             let addedCount = 0;
             let duplicateCount = 0;
@@ -299,6 +339,11 @@ export function useMonitors() {
             unlisten.then(fn => fn());
             unlistenAutoCheck.then(fn => fn());
             unlistenUpdated.then(fn => fn());
+            unlistenStorageError.then(fn => fn());
+            unlistenSettings.then(fn => fn());
+            unlistenWorker.then(fn => fn());
+            unlistenAlerts.then(fn => fn());
+            unlistenWatchError.then(fn => fn());
             unlistenDragDrop.then(fn => fn());
         };
     }, [fetchMonitors, handleAdd]);
@@ -312,6 +357,19 @@ export function useMonitors() {
         const init = async () => {
             const loadedMonitors = await fetchMonitors();
             await api.getSettings().then(setSettings);
+            await api.getWorkerStatus().then(setWorkerStatus);
+            await api.getAlerts().then(setAlerts).catch(console.error);
+
+            const warnings = await api.getStorageWarnings().catch(() => []);
+            for (const message of warnings) {
+                notifications.show({
+                    title: "Configuration Recovered",
+                    message,
+                    color: "orange",
+                    icon: <IconAlertTriangle size={16} />,
+                    autoClose: false
+                });
+            }
 
             const { getCurrentWindow } = await import("@tauri-apps/api/window");
             await getCurrentWindow().show();
@@ -323,6 +381,14 @@ export function useMonitors() {
         init();
     }, [fetchMonitors, scanAllInternal]);
 
+    const snoozeAlert = useCallback((id: string, hours: number) => {
+        api.snoozeAlert(id, hours).catch(e => showStorageError(errorMessage(e)));
+    }, []);
+
+    const acknowledgeAlert = useCallback((id: string) => {
+        api.acknowledgeAlert(id).catch(e => showStorageError(errorMessage(e)));
+    }, []);
+
     const stats = useMemo(() => calculateStats(monitors), [monitors]);
 
     return {
@@ -330,8 +396,15 @@ export function useMonitors() {
         scanning,
         settings,
         lastAutoCheck,
+        workerStatus,
+        alerts,
         stats,
         actions: {
+            checkNow: api.triggerBackgroundCheck,
+            pauseMonitoring: api.pauseMonitoring,
+            resumeMonitoring: api.resumeMonitoring,
+            snoozeAlert,
+            acknowledgeAlert,
             scanAll,
             scanOneStreaming,
             handleUpdateSettings,
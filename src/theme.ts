@@ -1,3 +1,5 @@
+import type { AlertLevel } from './types';
+
 export const AppColors = {
     primary: 'blue',
     secondary: 'gray',
@@ -26,14 +28,13 @@ export const getStatusColor = (
     enabled: boolean,
     loading: boolean,
     error: string | null | undefined,
-    currentMB: number,
-    threshold: number
+    level: AlertLevel
 ): string => {
     if (!enabled) return AppColors.neutral;
     if (loading) return AppColors.neutral;
     if (error) return AppColors.danger;
-    if (currentMB > threshold) return AppColors.danger;
-    if (currentMB > threshold * 0.8) return AppColors.warning;
+    if (level === "critical") return AppColors.danger;
+    if (level === "warning") return AppColors.warning;
     return AppColors.success;
 };
 
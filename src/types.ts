@@ -7,11 +7,63 @@ export type MonitorConfig = {
     notify: boolean;
     maxDepth?: number; // undefined or 0 = unlimited
     lastScanAt?: number;
+    largestFilesLimit?: number; // undefined or 0 = don't collect
+    extensionStats?: boolean;
+    ageHistogram?: boolean;
+    staleAfterDays?: number; // undefined or 0 = off
+    sizeMode?: SizeMode; // undefined = "logical"
+    dedupeHardlinks?: boolean;
+    followSymlinks?: boolean;
+    sameFilesystem?: boolean;
+    exclude?: string[]; // glob patterns
+    include?: string[]; // glob patterns, empty = every file
+    incremental?: boolean; // background scans only re-list changed folders
+    watch?: boolean; // keep the size current from file change notifications
+    intervalMinutes?: number; // undefined or 0 = global check interval
+    schedule?: string; // cron expression, overrides the interval
+    nextScanAt?: number;
+    growthAlert?: GrowthAlert;
+    levels?: ThresholdLevel[]; // empty = warning at 80%, critical at 100%
+    maxFileCount?: number; // undefined or 0 = no file count limit
+    mutedAt?: number; // last time a toast's mute action turned notify off
 };
 
+export type AlertLevel = "ok" | "warning" | "critical";
+
+/** A level reached at `percent` of the threshold. */
+export type ThresholdLevel = {
+    level: AlertLevel;
+    percent: number;
+    notify: boolean;
+    message?: string | null; // replaces the default notification text
+};
+
+/** Alert when a monitor grows by more than `mb` within `hours`. */
+export type GrowthAlert = {
+    mb: number;
+    hours: number;
+};
+
+export type SizeMode = "logical" | "allocated";
+
 export type MonitorStatus = MonitorConfig & {
     currentSizeBytes?: number;
     fileCount?: number;
+    largestFiles?: LargeFile[];
+    extensions?: ExtensionStat[];
+    ageBuckets?: AgeBucket[];
+    staleBytes?: number;
+    logicalBytes?: number;
+    allocatedBytes?: number;
+    hardlinkBytesSaved?: number;
+    partial?: boolean; // some entries couldn't be read, size is a lower bound
+    skippedCount?: number;
+    skippedSamples?: SkippedEntry[];
+    growthBytesPerDay?: number;
+    thresholdEta?: number; // when the threshold is predicted to be passed
+    level?: AlertLevel;
+    previousSizeBytes?: number; // totals from before the running scan, restored if it is cancelled
+    previousFileCount?: number;
     loading: boolean;
     error?: string | null;
 };
@@ -19,6 +71,51 @@ export type MonitorStatus = MonitorConfig & {
 export type AppSettings = {
     minimizeToTray: boolean;
     checkIntervalMinutes: number;
+    scanWorkers: number; // 0 = one per CPU core
+    renotifyMinutes: number; // 0 = only when an alert starts or gets worse
+    hysteresisPercent: number; // how far under a level the size must drop to clear it
+};
+
+export type AlertState = "ok" | "firing" | "acknowledged" | "resolved";
+
+/** Threshold alert of one monitor, keyed by monitor id. */
+/** One thing a monitor alerts about, and how far along that alert is. */
+export type AlertCondition = {
+    state: AlertState;
+    changedAt: number;
+    notifiedAt?: number | null;
+};
+
+/** The threshold alert's fields sit at the top level, next to the level it reached. */
+export type Alert = AlertCondition & {
+    level: AlertLevel;
+    snoozedUntil?: number | null;
+    growth?: AlertCondition;
+    fileCount?: AlertCondition;
+};
+
+/** One completed scan, or the average of several for older hours and days. */
+export type HistoryPoint = {
+    timestamp: number;
+    sizeBytes: number;
+    fileCount: number;
+    durationMs: number;
+    samples: number;
+};
+
+export type HistoryRange = "day" | "week" | "month" | "year" | "all";
+
+export type MistErrorKind = "io" | "parse" | "pathNotFound" | "permissionDenied" | "invalidConfig" | "scan";
+
+/** Error returned by backend commands. */
+export type MistError = {
+    kind: MistErrorKind;
+    message: string;
+};
+
+export type WorkerStatus = {
+    paused: boolean;
+    pausedUntil?: number; // undefined while paused = until resumed
 };
 
 export type ScanProgress = {
@@ -26,6 +123,59 @@ export type ScanProgress = {
     sizeBytes: number;
     fileCount: number;
     done: boolean;
+    cancelled?: boolean;
     error: string | null;
     lastScanAt?: number;
+    largestFiles?: LargeFile[] | null;
+    extensions?: ExtensionStat[] | null;
+    ageBuckets?: AgeBucket[] | null;
+    staleBytes?: number | null;
+    logicalBytes?: number | null;
+    allocatedBytes?: number | null;
+    hardlinkBytesSaved?: number | null;
+    partial?: boolean;
+    skippedCount?: number | null;
+    skippedSamples?: SkippedEntry[] | null;
+    reusedDirs?: number | null;
+    growthBytesPerDay?: number | null;
+    thresholdEta?: number | null;
+    level?: AlertLevel | null;
+    live?: boolean; // update from watch mode, not a scan
+};
+
+export type SizeNode = {
+    name: string;
+    path: string;
+    sizeBytes: number;
+    fileCount: number;
+    children: SizeNode[];
+};
+
+export type LargeFile = {
+    path: string;
+    sizeBytes: number;
+    modifiedAt?: number | null;
+};
+
+export type ExtensionStat = {
+    extension: string;
+    sizeBytes: number;
+    fileCount: number;
+};
+
+export type AgeBucket = {
+    label: string;
+    maxAgeDays?: number | null; // null for the open-ended last bucket
+    sizeBytes: number;
+    fileCount: number;
+};
+
+export type WatchError = {
+    monitorId: string;
+    error: string;
+};
+
+export type SkippedEntry = {
+    path: string;
+    error: string;
 };
@@ -1,5 +1,16 @@
 import { open as openDialog } from '@tauri-apps/plugin-dialog';
-import type { MonitorConfig, MonitorStatus, ScanProgress } from './types';
+import type { AlertLevel, MistError, MonitorConfig, MonitorStatus, ScanProgress } from './types';
+
+/** Readable text for an error thrown by a backend command, or anything else. */
+export const errorMessage = (error: unknown): string => {
+    if (error && typeof error === 'object' && 'kind' in error && 'message' in error) {
+        const { kind, message } = error as MistError;
+        if (kind === 'pathNotFound') return `Folder not found: ${message}`;
+        if (kind === 'permissionDenied') return `Permission denied: ${message}`;
+        return message;
+    }
+    return String(error);
+};
 
 export const formatBytes = (bytes?: number): string => {
     if (bytes === undefined) return "---";
@@ -57,14 +68,43 @@ export const updateMonitorWithProgress = (
 ): MonitorStatus[] => {
     return monitors.map(m => {
         if (m.id === progress.monitorId) {
+            // A cancelled scan only counted part of the tree, go back to the last full result
+            if (progress.cancelled) {
+                return {
+                    ...m,
+                    currentSizeBytes: m.previousSizeBytes ?? m.currentSizeBytes,
+                    fileCount: m.previousFileCount ?? m.fileCount,
+                    previousSizeBytes: undefined,
+                    previousFileCount: undefined,
+                    loading: false
+                };
+            }
+            // Running counts replace the totals while a scan walks, so keep the
+            // last full ones until it finishes
+            const counting = !progress.done && !progress.live;
             return {
                 ...m,
                 currentSizeBytes: progress.sizeBytes,
                 fileCount: progress.fileCount,
+                previousSizeBytes: counting ? m.previousSizeBytes ?? m.currentSizeBytes : undefined,
+                previousFileCount: counting ? m.previousFileCount ?? m.fileCount : undefined,
                 error: progress.error,
                 // Only set loading to false when done, don't change it on progress updates
                 loading: progress.done ? false : m.loading,
-                lastScanAt: progress.lastScanAt ?? m.lastScanAt
+                lastScanAt: progress.lastScanAt ?? m.lastScanAt,
+                largestFiles: progress.largestFiles ?? m.largestFiles,
+                extensions: progress.extensions ?? m.extensions,
+                ageBuckets: progress.ageBuckets ?? m.ageBuckets,
+                staleBytes: progress.staleBytes ?? m.staleBytes,
+                logicalBytes: progress.logicalBytes ?? m.logicalBytes,
+                allocatedBytes: progress.allocatedBytes ?? m.allocatedBytes,
+                hardlinkBytesSaved: progress.hardlinkBytesSaved ?? m.hardlinkBytesSaved,
+                partial: progress.done ? progress.partial : m.partial,
+                skippedCount: progress.skippedCount ?? m.skippedCount,
+                skippedSamples: progress.skippedSamples ?? m.skippedSamples,
+                growthBytesPerDay: progress.done && !progress.live ? progress.growthBytesPerDay ?? undefined : m.growthBytesPerDay,
+                thresholdEta: progress.done && !progress.live ? progress.thresholdEta ?? undefined : m.thresholdEta,
+                level: progress.level ?? m.level
             };
         }
         return m;
@@ -105,6 +145,13 @@ export const configToStatus = (monitors: MonitorConfig[]): MonitorStatus[] => {
     }));
 };
 
+// Level for monitors that haven't been scanned since levels were added
+export const levelFromSize = (currentMB: number, threshold: number): AlertLevel => {
+    if (currentMB > threshold) return "critical";
+    if (currentMB > threshold * 0.8) return "warning";
+    return "ok";
+};
+
 // Calculate stats from monitors
 export const calculateStats = (monitors: MonitorStatus[]): { totalSize: number; criticalCount: number } => {
     let totalSize = 0;
@@ -113,7 +160,7 @@ export const calculateStats = (monitors: MonitorStatus[]): { totalSize: number;
         if (m.enabled) {
             if (m.currentSizeBytes) totalSize += m.currentSizeBytes;
             const mb = (m.currentSizeBytes || 0) / (1024 * 1024);
-            if (mb > m.threshold) criticalCount++;
+            if ((m.level ?? levelFromSize(mb, m.threshold)) === "critical") criticalCount++;
         }
     });
     return { totalSize, criticalCount };